@@ -0,0 +1,77 @@
+use crate::{vec2::Vec2, image::{Image, BlockSample}, utils::flatten_2d_index};
+
+/// Fraction of area covered by tiles for every generator block of truchet image.
+///
+/// Blocks are stored by columns, value of the block at `(x, y)` has index `x * size.y() + y`.
+#[derive(Clone, Debug)]
+pub struct CoverageMap {
+    size: Vec2<usize>,
+    source_block_size: Vec2<usize>,
+    coverage: Vec<f32>
+}
+
+/// Difference between rendered and source brightness, positive values mean output is lighter than source.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ToneError {
+    pub mean: f32,
+    pub mean_absolute: f32,
+    pub root_mean_square: f32,
+    pub max_absolute: f32
+}
+
+impl CoverageMap {
+    pub(crate) fn new(size: Vec2<usize>, source_block_size: Vec2<usize>, coverage: Vec<f32>) -> Self {
+        return Self { size, source_block_size, coverage };
+    }
+
+    /// Number of blocks in each direction.
+    #[inline]
+    pub fn size(&self) -> Vec2<usize> {
+        return self.size;
+    }
+
+    /// Coverage of the block, `pos` is block index along `x` and `y` axes of the source image.
+    #[inline]
+    pub fn get(&self, pos: Vec2<usize>) -> f32 {
+        return self.coverage[flatten_2d_index(pos.x(), pos.y(), self.size.y())];
+    }
+
+    /// Mean coverage of the whole image.
+    pub fn mean(&self) -> f32 {
+        if self.coverage.is_empty() {
+            return 0.0;
+        }
+
+        return self.coverage.iter().sum::<f32>() / self.coverage.len() as f32;
+    }
+
+    /// Compares rendered brightness of every block with mean brightness of the source image block it was generated from.
+    pub fn tone_error<TImage: Image>(&self, image: &TImage) -> ToneError {
+        if self.coverage.is_empty() {
+            return ToneError::default();
+        }
+
+        let mut error = ToneError::default();
+        let mut squares_sum = 0.0;
+
+        for x in 0..self.size.x() {
+            for y in 0..self.size.y() {
+                let pos = Vec2::new(x, y);
+                let source = BlockSample::sample(image, pos * self.source_block_size, self.source_block_size).brightness();
+                let diff = (1.0 - self.get(pos)) - source;
+
+                error.mean += diff;
+                error.mean_absolute += diff.abs();
+                error.max_absolute = error.max_absolute.max(diff.abs());
+                squares_sum += diff * diff;
+            }
+        }
+
+        let count = self.coverage.len() as f32;
+        error.mean /= count;
+        error.mean_absolute /= count;
+        error.root_mean_square = (squares_sum / count).sqrt();
+
+        return error;
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{vec2::Vec2, utils::flatten_2d_index};
+
+/// Method of distributing quantization error when tone is reproduced with discrete brightness levels.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Dithering {
+    /// Error diffusion to 4 neighbours.
+    #[default]
+    FloydSteinberg,
+    /// Error diffusion to 6 neighbours which drops a quarter of the error, gives higher contrast.
+    Atkinson,
+    /// Threshold with 4x4 Bayer matrix, does not diffuse error so patterns are regular.
+    Ordered
+}
+
+const BAYER_4X4: [[u8; 4]; 4] = [
+    [0, 8, 2, 10],
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5]
+];
+
+/// Replaces every value of the grid with one of the brightness levels.
+///
+/// Grid is stored by columns, value at `(x, y)` has index `x * size.y() + y`. Levels must be sorted and not empty.
+pub fn dither(values: &mut [f32], size: Vec2<usize>, levels: &[f32], dithering: Dithering) {
+    let (width, height) = (size.x(), size.y());
+    let diffuse = |values: &mut [f32], x: usize, y: usize, dx: isize, dy: usize, error: f32| {
+        let x = x as isize + dx;
+
+        if x >= 0 && (x as usize) < width && y + dy < height {
+            values[flatten_2d_index(x as usize, y + dy, height)] += error;
+        }
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let index = flatten_2d_index(x, y, height);
+            let value = values[index];
+            let level = match dithering {
+                Dithering::Ordered => ordered_level(value, levels, BAYER_4X4[y % 4][x % 4]),
+                _ => nearest_level(value, levels),
+            };
+            let error = value - level;
+            values[index] = level;
+
+            match dithering {
+                Dithering::FloydSteinberg => {
+                    diffuse(values, x, y, 1, 0, error * 7.0 / 16.0);
+                    diffuse(values, x, y, -1, 1, error * 3.0 / 16.0);
+                    diffuse(values, x, y, 0, 1, error * 5.0 / 16.0);
+                    diffuse(values, x, y, 1, 1, error / 16.0);
+                },
+                Dithering::Atkinson => {
+                    for (dx, dy) in [(1, 0), (2, 0), (-1, 1), (0, 1), (1, 1), (0, 2)] {
+                        diffuse(values, x, y, dx, dy, error / 8.0);
+                    }
+                },
+                Dithering::Ordered => {}
+            }
+        }
+    }
+}
+
+fn nearest_level(value: f32, levels: &[f32]) -> f32 {
+    return levels.iter()
+        .copied()
+        .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+        .unwrap();
+}
+
+fn ordered_level(value: f32, levels: &[f32], threshold_rank: u8) -> f32 {
+    let upper = levels.partition_point(|level| *level < value);
+
+    if upper == 0 {
+        return levels[0];
+    }
+
+    if upper == levels.len() {
+        return levels[levels.len() - 1];
+    }
+
+    let (lower_level, upper_level) = (levels[upper - 1], levels[upper]);
+    let fraction = (value - lower_level) / (upper_level - lower_level);
+    let threshold = (threshold_rank as f32 + 0.5) / 16.0;
+
+    return if fraction > threshold { upper_level } else { lower_level };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec2::Vec2;
+
+    use super::{dither, Dithering};
+
+    fn dithered(values: &[f32], size: Vec2<usize>, dithering: Dithering) -> Vec<f32> {
+        let mut values = values.to_vec();
+        dither(&mut values, size, &[0.0, 1.0], dithering);
+        return values;
+    }
+
+    #[test]
+    fn kernels_distribute_error_differently() {
+        let size = Vec2::new(2, 1);
+
+        // 7/16 of the error pushes the right value over the threshold
+        assert_eq!(dithered(&[0.4, 0.4], size, Dithering::FloydSteinberg), vec![0.0, 1.0]);
+        // Only 1/8 of the error reaches the right value
+        assert_eq!(dithered(&[0.4, 0.4], size, Dithering::Atkinson), vec![0.0, 0.0]);
+        // Thresholds of the first Bayer row are 1/32 and 17/32
+        assert_eq!(dithered(&[0.4, 0.4], size, Dithering::Ordered), vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn values_are_replaced_with_levels() {
+        for dithering in [Dithering::FloydSteinberg, Dithering::Atkinson, Dithering::Ordered] {
+            let values = dithered(&[0.25; 64], Vec2::new(8, 8), dithering);
+
+            assert!(values.iter().all(|value| *value == 0.0 || *value == 1.0));
+        }
+    }
+
+    #[test]
+    fn tone_is_kept_without_dropped_error() {
+        // Atkinson drops a quarter of the error, so its tone is not kept
+        for dithering in [Dithering::FloydSteinberg, Dithering::Ordered] {
+            let values = dithered(&[0.25; 64], Vec2::new(8, 8), dithering);
+            let mean = values.iter().sum::<f32>() / values.len() as f32;
+
+            assert!((mean - 0.25).abs() < 0.05, "{:?} gives mean {}", dithering, mean);
+        }
+    }
+}
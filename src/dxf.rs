@@ -0,0 +1,441 @@
+use std::io::{self, Write};
+
+use crate::{vec2::Vec2, geometry::{Outline, Segment, CenterArc}, generator::Generator, tile::traits::Tile, truchet_image::TruchetImage};
+
+/// Distance used to find on which side of the outline edge the filled area lies.
+const SIDE_PROBE: f32 = 1e-3;
+/// Offset of sharp corners is limited to this many offset distances.
+const MITER_LIMIT: f32 = 4.0;
+
+// Handles of the tables, blocks and dictionaries every drawing has, layers and entities are numbered after them
+const LTYPE_TABLE: usize = 0x1;
+const LTYPES: [(usize, &str); 3] = [(0x2, "ByBlock"), (0x3, "ByLayer"), (0x4, "Continuous")];
+const LAYER_TABLE: usize = 0x5;
+const BLOCK_RECORD_TABLE: usize = 0x6;
+const MODEL_SPACE: usize = 0x7;
+const PAPER_SPACE: usize = 0x8;
+const BLOCKS: [(usize, usize, &str); 2] = [(0x9, MODEL_SPACE, "*Model_Space"), (0xB, PAPER_SPACE, "*Paper_Space")];
+const ROOT_DICTIONARY: usize = 0xD;
+const GROUP_DICTIONARY: usize = 0xE;
+const FIRST_HANDLE: usize = 0x10;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DxfUnits {
+    #[default]
+    Millimetres,
+    Centimetres,
+    Inches
+}
+
+impl DxfUnits {
+    /// Value of `$INSUNITS` header variable.
+    fn code(&self) -> &'static str {
+        return match self {
+            DxfUnits::Millimetres => "4",
+            DxfUnits::Centimetres => "5",
+            DxfUnits::Inches => "1",
+        };
+    }
+
+    /// Value of `$MEASUREMENT` header variable, 0 for imperial and 1 for metric units.
+    fn measurement(&self) -> &'static str {
+        return match self {
+            DxfUnits::Inches => "0",
+            _ => "1",
+        };
+    }
+}
+
+/// How shapes are split between layers.
+pub enum DxfLayers<TTile> {
+    /// All shapes on the single layer.
+    Single,
+    /// Layer per band of tile coverage, the darkest tiles are on the last layer.
+    Coverage(usize),
+    /// Layer named by the function of the tile, e.g. one layer per tile type.
+    ByTile(fn(&TTile) -> String)
+}
+
+/// Settings of the exported drawing, lengths are in `units`.
+pub struct DxfOptions<TTile> {
+    tile_size: f32,
+    units: DxfUnits,
+    kerf: f32,
+    layers: DxfLayers<TTile>
+}
+
+impl<TTile: Tile> DxfOptions<TTile> {
+    pub fn new(tile_size: f32, units: DxfUnits) -> Self {
+        return Self { tile_size, units, kerf: 0.0, layers: DxfLayers::Single };
+    }
+
+    /// Width of the cut, shapes are offset by half of the kerf away from the filled area so parts keep their size.
+    ///
+    /// Offset shapes are made of straight lines only.
+    pub fn with_kerf(mut self, kerf: f32) -> Self {
+        self.kerf = kerf.max(0.0);
+        return self;
+    }
+
+    pub fn with_layers(mut self, layers: DxfLayers<TTile>) -> Self {
+        self.layers = layers;
+        return self;
+    }
+
+    fn layer(&self, tile: &TTile) -> String {
+        return match self.layers {
+            DxfLayers::Single => "TRUCHET".to_owned(),
+            DxfLayers::Coverage(bands) => {
+                let bands = bands.max(1);
+                let band = ((tile.coverage() * bands as f32) as usize).min(bands - 1);
+                format!("COVERAGE_{}", band)
+            },
+            DxfLayers::ByTile(name) => name(tile),
+        };
+    }
+}
+
+/// Writes tile outlines as closed lightweight polylines of an AutoCAD 2000 (AC1015) DXF drawing,
+/// arcs are kept as polyline bulges.
+///
+/// Every tile is cut separately, so shapes crossing sides of the cells are split at the sides.
+/// Coordinates are tile positions multiplied by `tile_size`, units are set by `$INSUNITS` header variable.
+/// Drawing is streamed tile by tile after outlines are counted for the handles, `y` axis points up as usual in CAD.
+pub fn write_dxf<TGenerator: Generator, W: Write>(image: &TruchetImage<TGenerator>, options: &DxfOptions<TGenerator::TileType>, mut writer: W) -> io::Result<()> {
+    let grid_size = image.grid_size();
+    let kerf = options.kerf / 2.0 / options.tile_size;
+
+    // Layer "0" is required in every drawing
+    let mut layers = vec!["0".to_owned()];
+    let mut polylines_count = 0;
+    for placed in image.tiles() {
+        let layer = options.layer(placed.tile);
+        if !layers.contains(&layer) {
+            layers.push(layer);
+        }
+        polylines_count += tile_polylines(&placed.outlines(), kerf).len();
+    }
+
+    let handle = |handle: usize| format!("{:X}", handle);
+    write_pairs(&mut writer, &[
+        (0, "SECTION"), (2, "HEADER"),
+        (9, "$ACADVER"), (1, "AC1015"),
+        (9, "$HANDSEED"), (5, &handle(FIRST_HANDLE + layers.len() + polylines_count)),
+        (9, "$INSUNITS"), (70, options.units.code()),
+        (9, "$MEASUREMENT"), (70, options.units.measurement()),
+        (0, "ENDSEC"),
+        (0, "SECTION"), (2, "TABLES")
+    ])?;
+
+    write_table(&mut writer, "LTYPE", LTYPE_TABLE, LTYPES.len())?;
+    for (ltype, name) in LTYPES {
+        write_pairs(&mut writer, &[
+            (0, "LTYPE"), (5, &handle(ltype)), (330, &handle(LTYPE_TABLE)),
+            (100, "AcDbSymbolTableRecord"), (100, "AcDbLinetypeTableRecord"),
+            (2, name), (70, "0"), (3, ""), (72, "65"), (73, "0"), (40, "0.0")
+        ])?;
+    }
+    write_pairs(&mut writer, &[(0, "ENDTAB")])?;
+
+    write_table(&mut writer, "LAYER", LAYER_TABLE, layers.len())?;
+    for (i, layer) in layers.iter().enumerate() {
+        // Every layer gets its own colour from the standard palette
+        let colour = (i % 255 + 1).to_string();
+        write_pairs(&mut writer, &[
+            (0, "LAYER"), (5, &handle(FIRST_HANDLE + i)), (330, &handle(LAYER_TABLE)),
+            (100, "AcDbSymbolTableRecord"), (100, "AcDbLayerTableRecord"),
+            (2, layer), (70, "0"), (62, &colour), (6, "Continuous")
+        ])?;
+    }
+    write_pairs(&mut writer, &[(0, "ENDTAB")])?;
+
+    write_table(&mut writer, "BLOCK_RECORD", BLOCK_RECORD_TABLE, BLOCKS.len())?;
+    for (_, record, name) in BLOCKS {
+        write_pairs(&mut writer, &[
+            (0, "BLOCK_RECORD"), (5, &handle(record)), (330, &handle(BLOCK_RECORD_TABLE)),
+            (100, "AcDbSymbolTableRecord"), (100, "AcDbBlockTableRecord"), (2, name)
+        ])?;
+    }
+    write_pairs(&mut writer, &[(0, "ENDTAB"), (0, "ENDSEC"), (0, "SECTION"), (2, "BLOCKS")])?;
+
+    for (block, record, name) in BLOCKS {
+        let paper_space = if record == PAPER_SPACE { "1" } else { "0" };
+        write_pairs(&mut writer, &[
+            (0, "BLOCK"), (5, &handle(block)), (330, &handle(record)), (100, "AcDbEntity"), (67, paper_space), (8, "0"),
+            (100, "AcDbBlockBegin"), (2, name), (70, "0"), (10, "0.0"), (20, "0.0"), (30, "0.0"), (3, name), (1, ""),
+            (0, "ENDBLK"), (5, &handle(block + 1)), (330, &handle(record)), (100, "AcDbEntity"), (67, paper_space), (8, "0"),
+            (100, "AcDbBlockEnd")
+        ])?;
+    }
+    write_pairs(&mut writer, &[(0, "ENDSEC"), (0, "SECTION"), (2, "ENTITIES")])?;
+
+    let height = grid_size.x() as f32;
+    let mut entity = FIRST_HANDLE + layers.len();
+    for placed in image.tiles() {
+        let layer = options.layer(placed.tile);
+
+        for polyline in tile_polylines(&placed.outlines(), kerf) {
+            let vertices: Vec<(Vec2<f32>, f32)> = polyline.into_iter()
+                .map(|(p, bulge)| (Vec2::new(p.x(), height - p.y()) * options.tile_size, bulge))
+                .collect();
+            write_polyline(&mut writer, &handle(entity), &layer, &vertices)?;
+            entity += 1;
+        }
+    }
+
+    write_pairs(&mut writer, &[
+        (0, "ENDSEC"),
+        (0, "SECTION"), (2, "OBJECTS"),
+        (0, "DICTIONARY"), (5, &handle(ROOT_DICTIONARY)), (330, "0"), (100, "AcDbDictionary"), (281, "1"),
+        (3, "ACAD_GROUP"), (350, &handle(GROUP_DICTIONARY)),
+        (0, "DICTIONARY"), (5, &handle(GROUP_DICTIONARY)), (330, &handle(ROOT_DICTIONARY)), (100, "AcDbDictionary"), (281, "1"),
+        (0, "ENDSEC"),
+        (0, "EOF")
+    ])?;
+    return writer.flush();
+}
+
+/// Closed polylines of tile outlines, offset away from the filled area when `kerf` is set.
+fn tile_polylines(outlines: &[Outline], kerf: f32) -> Vec<Vec<(Vec2<f32>, f32)>> {
+    return outlines.iter()
+        .flat_map(|outline| if kerf > 0.0 { offset_polylines(outline, kerf) } else { polylines(outline) })
+        .collect();
+}
+
+fn write_pairs<W: Write>(writer: &mut W, pairs: &[(u16, &str)]) -> io::Result<()> {
+    for (code, value) in pairs {
+        write!(writer, "{}\n{}\n", code, value)?;
+    }
+
+    return Ok(());
+}
+
+/// Header of the symbol table with the given number of entries, owned by nothing.
+fn write_table<W: Write>(writer: &mut W, name: &str, handle: usize, entries: usize) -> io::Result<()> {
+    return write_pairs(writer, &[
+        (0, "TABLE"), (2, name), (5, &format!("{:X}", handle)), (330, "0"), (100, "AcDbSymbolTable"), (70, &entries.to_string())
+    ]);
+}
+
+fn write_polyline<W: Write>(writer: &mut W, handle: &str, layer: &str, vertices: &[(Vec2<f32>, f32)]) -> io::Result<()> {
+    write_pairs(writer, &[
+        (0, "LWPOLYLINE"), (5, handle), (330, &format!("{:X}", MODEL_SPACE)), (100, "AcDbEntity"), (8, layer),
+        (100, "AcDbPolyline"), (90, &vertices.len().to_string()), (70, "1")
+    ])?;
+    for (p, bulge) in vertices {
+        write_pairs(writer, &[(10, &p.x().to_string()), (20, &p.y().to_string())])?;
+        if *bulge != 0.0 {
+            write_pairs(writer, &[(42, &bulge.to_string())])?;
+        }
+    }
+
+    return Ok(());
+}
+
+/// Closed polylines of subpaths as vertices with bulges of the following pieces, bulges are given for `y` axis pointing up.
+fn polylines(outline: &Outline) -> Vec<Vec<(Vec2<f32>, f32)>> {
+    let mut polylines = Vec::new();
+    let mut current: Vec<(Vec2<f32>, f32)> = Vec::new();
+    let mut position = Vec2::new(0.0, 0.0);
+
+    for segment in outline.segments() {
+        match *segment {
+            Segment::MoveTo(p) => {
+                polylines.push(std::mem::take(&mut current));
+                current.push((p, 0.0));
+                position = p;
+            },
+            Segment::LineTo(p) => {
+                current.push((p, 0.0));
+                position = p;
+            },
+            Segment::ArcTo { radii, x_rotation, large_arc, sweep, end } if radii.x() == radii.y() => {
+                let arc = CenterArc::from_endpoints(position, end, radii, x_rotation, large_arc, sweep);
+                if let Some(last) = current.last_mut() {
+                    // Sweep angle is measured with `y` pointing down, flipping the axis reverses the arc
+                    last.1 = -(arc.sweep_angle / 4.0).tan();
+                }
+                current.push((end, 0.0));
+                position = end;
+            },
+            Segment::ArcTo { end, .. } | Segment::CubicTo(_, _, end) => {
+                let flattened = Outline::from_segments(vec![Segment::MoveTo(position), *segment]).polygons();
+                current.extend(flattened.into_iter().flatten().skip(1).map(|p| (p, 0.0)));
+                position = end;
+            },
+            Segment::Close => {
+                if let Some(&(first, _)) = current.first() {
+                    position = first;
+                }
+                polylines.push(std::mem::take(&mut current));
+                current.push((position, 0.0));
+            },
+        }
+    }
+    polylines.push(current);
+
+    // Polylines are closed by a flag, so repeated start point is dropped
+    for polyline in &mut polylines {
+        if polyline.len() > 1 && distance(polyline[0].0, polyline[polyline.len() - 1].0) < 1e-6 {
+            polyline.pop();
+        }
+    }
+    polylines.retain(|polyline| polyline.len() > 1);
+
+    return polylines;
+}
+
+/// Flattened subpaths offset away from the filled area of the outline.
+fn offset_polylines(outline: &Outline, distance: f32) -> Vec<Vec<(Vec2<f32>, f32)>> {
+    return outline.polygons()
+        .into_iter()
+        .filter_map(|mut polygon| {
+            polygon.dedup_by(|a, b| self::distance(*a, *b) < 1e-6);
+            while polygon.len() > 1 && self::distance(polygon[0], polygon[polygon.len() - 1]) < 1e-6 {
+                polygon.pop();
+            }
+            if polygon.len() < 3 {
+                return None;
+            }
+
+            // Filled area is probed next to the first edge, all edges of the subpath have it on the same side
+            let normal = |a: Vec2<f32>, b: Vec2<f32>| {
+                let d = b - a;
+                let length = self::distance(a, b);
+                return Vec2::new(-d.y() / length, d.x() / length);
+            };
+            let middle = (polygon[0] + polygon[1]) * 0.5;
+            let side = if outline.contains(middle + normal(polygon[0], polygon[1]) * SIDE_PROBE) { -1.0 } else { 1.0 };
+
+            let n = polygon.len();
+            let offset = (0..n)
+                .map(|i| {
+                    let (prev, p, next) = (polygon[(i + n - 1) % n], polygon[i], polygon[(i + 1) % n]);
+                    let (n1, n2) = (normal(prev, p) * side, normal(p, next) * side);
+                    let miter = (n1 + n2) * (distance / (1.0 + n1.x() * n2.x() + n1.y() * n2.y()).max(f32::EPSILON));
+                    let length = self::distance(miter, Vec2::new(0.0, 0.0));
+                    let miter = if length > MITER_LIMIT * distance { miter * (MITER_LIMIT * distance / length) } else { miter };
+
+                    return (p + miter, 0.0);
+                })
+                .collect();
+
+            return Some(offset);
+        })
+        .collect();
+}
+
+#[inline]
+fn distance(a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+    let d = a - b;
+    return (d.x() * d.x() + d.y() * d.y()).sqrt();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generator, image::fixtures::Uniform, truchet_image::generate, vec2::Vec2};
+
+    use super::{write_dxf, DxfOptions, DxfUnits};
+
+    fn pairs(dxf: &str) -> Vec<(u16, &str)> {
+        let lines: Vec<&str> = dxf.lines().collect();
+        return lines.chunks(2).map(|pair| (pair[0].trim().parse().unwrap(), pair[1].trim())).collect();
+    }
+
+    /// Value following the header variable.
+    fn header_variable<'a>(dxf: &'a str, name: &str) -> &'a str {
+        let pairs = pairs(dxf);
+        let index = pairs.iter().position(|pair| *pair == (9, name)).unwrap();
+        return pairs[index + 1].1;
+    }
+
+    /// Closed flags and vertices of every polyline parsed back from the group code pairs.
+    fn parse_polylines(dxf: &str) -> Vec<(bool, Vec<Vec2<f32>>)> {
+        let mut polylines: Vec<(bool, Vec<Vec2<f32>>)> = Vec::new();
+        let mut entity = "";
+        let mut x = 0.0;
+
+        for (code, value) in pairs(dxf) {
+            match (code, entity) {
+                (0, _) => {
+                    entity = value;
+                    if value == "LWPOLYLINE" {
+                        polylines.push((false, Vec::new()));
+                    }
+                },
+                (70, "LWPOLYLINE") => polylines.last_mut().unwrap().0 = value == "1",
+                (10, "LWPOLYLINE") => x = value.parse().unwrap(),
+                (20, "LWPOLYLINE") => polylines.last_mut().unwrap().1.push(Vec2::new(x, value.parse().unwrap())),
+                _ => {}
+            }
+        }
+
+        return polylines;
+    }
+
+    fn write(units: DxfUnits, kerf: f32) -> String {
+        let image = generate(&Uniform(Vec2::new(8, 8), 0.5), generator::fan(Vec2::new(4, 4)));
+        let mut dxf = Vec::new();
+        write_dxf(&image, &DxfOptions::new(10.0, units).with_kerf(kerf), &mut dxf).unwrap();
+        return String::from_utf8(dxf).unwrap();
+    }
+
+    fn line_distance(p: Vec2<f32>, a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+        let (d, v) = (b - a, p - a);
+        return (d.x() * v.y() - d.y() * v.x()).abs() / (d.x() * d.x() + d.y() * d.y()).sqrt();
+    }
+
+    fn area(polygon: &[Vec2<f32>]) -> f32 {
+        let n = polygon.len();
+        return (0..n).map(|i| polygon[i].x() * polygon[(i + 1) % n].y() - polygon[(i + 1) % n].x() * polygon[i].y()).sum::<f32>().abs() / 2.0;
+    }
+
+    #[test]
+    fn polylines_are_closed_and_offset_by_half_of_kerf() {
+        let (exact, offset) = (write(DxfUnits::Millimetres, 0.0), write(DxfUnits::Millimetres, 0.2));
+        let (exact, offset) = (parse_polylines(&exact), parse_polylines(&offset));
+        assert!(!exact.is_empty());
+        assert_eq!(exact.len(), offset.len());
+
+        for ((exact_closed, exact), (offset_closed, offset)) in exact.iter().zip(offset.iter()) {
+            assert!(*exact_closed && *offset_closed);
+            assert_eq!(exact.len(), offset.len());
+            assert!(area(offset) > area(exact));
+
+            // Every edge is moved outwards by half of the kerf
+            let n = exact.len();
+            for i in 0..n {
+                let distance = line_distance(offset[i], exact[i], exact[(i + 1) % n]);
+                assert!((distance - 0.1).abs() < 1e-3, "vertex {} is {} away from its edge", i, distance);
+            }
+        }
+    }
+
+    #[test]
+    fn header_sets_units() {
+        for (units, code, measurement) in [(DxfUnits::Millimetres, "4", "1"), (DxfUnits::Centimetres, "5", "1"), (DxfUnits::Inches, "1", "0")] {
+            let dxf = write(units, 0.0);
+            assert_eq!(header_variable(&dxf, "$ACADVER"), "AC1015");
+            assert_eq!(header_variable(&dxf, "$INSUNITS"), code);
+            assert_eq!(header_variable(&dxf, "$MEASUREMENT"), measurement);
+        }
+    }
+
+    #[test]
+    fn handles_are_unique_and_below_seed() {
+        let dxf = write(DxfUnits::Millimetres, 0.0);
+        let seed = usize::from_str_radix(header_variable(&dxf, "$HANDSEED"), 16).unwrap();
+        let mut handles: Vec<usize> = pairs(&dxf).into_iter()
+            .skip_while(|pair| *pair != (0, "ENDSEC"))
+            .filter(|(code, _)| *code == 5)
+            .map(|(_, value)| usize::from_str_radix(value, 16).unwrap())
+            .collect();
+        let count = handles.len();
+        handles.sort();
+        handles.dedup();
+
+        assert_eq!(handles.len(), count);
+        assert!(handles.iter().all(|handle| *handle > 0 && *handle < seed));
+        assert_eq!(handles.last().unwrap() + 1, seed);
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::VecDeque;
+
+use rand::{Rng, seq::SliceRandom};
+
+use crate::{tile::{traits::Tile, edge::Side}, utils::flatten_2d_index, vec2::Vec2};
+
+/// Finds layout of candidate tiles on a grid wrapped into a torus so that all neighbouring
+/// tiles match along shared edges, see [`EdgeSolver::with_neighbours`] for grids without wrapping.
+///
+/// Uses wave function collapse: cell with the fewest remaining candidates is collapsed to random candidate,
+/// constraints are propagated to the neighbours and search backtracks on contradiction.
+pub struct EdgeSolver {
+    candidates_count: usize,
+    rows: usize,
+    cols: usize,
+    // compatible[side][a][b] - candidate `b` can be placed on the `side` of candidate `a`
+    compatible: [Vec<Vec<bool>>; 4],
+    attempts_left: usize,
+    // Fixed candidates above the first row and to the left of the first column, set when grid isn't wrapped
+    neighbours: Option<(Neighbours, Neighbours)>
+}
+
+type Domains = Vec<Vec<bool>>;
+type Neighbours = Vec<Option<usize>>;
+
+impl EdgeSolver {
+    pub fn new<TTile: Tile>(candidates: &[TTile], grid_size: Vec2<usize>) -> Self {
+        let edges: Vec<_> = candidates.iter().map(|tile| tile.edges()).collect();
+        let compatible = Side::ALL.map(|side| {
+            edges.iter()
+                .map(|a| edges.iter().map(|b| a.matches(side, b)).collect())
+                .collect()
+        });
+        let cells_count = grid_size.x() * grid_size.y();
+
+        return Self {
+            candidates_count: candidates.len(),
+            rows: grid_size.x(),
+            cols: grid_size.y(),
+            compatible,
+            attempts_left: 16 * cells_count.max(1),
+            neighbours: None
+        };
+    }
+
+    /// Solves grid without wrapping it around its borders, tiles of the first row and column have to match
+    /// candidates already placed above and to the left of them, `None` where there is no neighbour.
+    pub fn with_neighbours(mut self, above: Neighbours, left: Neighbours) -> Self {
+        assert_eq!(above.len(), self.cols, "Every column needs its neighbour above");
+        assert_eq!(left.len(), self.rows, "Every row needs its neighbour to the left");
+
+        self.neighbours = Some((above, left));
+        return self;
+    }
+
+    /// Returns index of candidate for every cell or `None` if no valid layout was found.
+    pub fn solve<R: Rng + ?Sized>(mut self, rng: &mut R) -> Option<Vec<usize>> {
+        let cells_count = self.rows * self.cols;
+
+        if self.candidates_count == 0 {
+            return if cells_count == 0 { Some(Vec::new()) } else { None };
+        }
+
+        let mut domains = vec![vec![true; self.candidates_count]; cells_count];
+        if let Some((above, left)) = &self.neighbours {
+            let fixed = above.iter().enumerate().map(|(col, candidate)| (flatten_2d_index(0, col, self.cols), Side::Bottom, candidate))
+                .chain(left.iter().enumerate().map(|(row, candidate)| (flatten_2d_index(row, 0, self.cols), Side::Right, candidate)));
+
+            for (cell, side, neighbour) in fixed {
+                if let Some(neighbour) = neighbour {
+                    domains[cell].iter_mut().enumerate()
+                        .for_each(|(candidate, allowed)| *allowed &= self.compatible[side as usize][*neighbour][candidate]);
+                }
+            }
+
+            if domains.iter().any(|domain| !domain.iter().any(|allowed| *allowed)) {
+                return None;
+            }
+        }
+
+        if !self.propagate(&mut domains, (0..cells_count).collect()) {
+            return None;
+        }
+
+        let solved = self.search(domains, rng)?;
+
+        return Some(
+            solved.iter()
+                .map(|domain| domain.iter().position(|allowed| *allowed).unwrap())
+                .collect()
+        );
+    }
+
+    fn search<R: Rng + ?Sized>(&mut self, domains: Domains, rng: &mut R) -> Option<Domains> {
+        let cell = domains.iter()
+            .enumerate()
+            .map(|(cell, domain)| (cell, domain.iter().filter(|allowed| **allowed).count()))
+            .filter(|(_, count)| *count > 1)
+            .min_by_key(|(_, count)| *count)
+            .map(|(cell, _)| cell);
+
+        let Some(cell) = cell else {
+            return Some(domains);
+        };
+
+        let mut options: Vec<usize> = (0..self.candidates_count)
+            .filter(|candidate| domains[cell][*candidate])
+            .collect();
+        options.shuffle(rng);
+
+        for option in options {
+            if self.attempts_left == 0 {
+                return None;
+            }
+
+            self.attempts_left -= 1;
+
+            let mut collapsed = domains.clone();
+            collapsed[cell].iter_mut().enumerate().for_each(|(candidate, allowed)| *allowed = candidate == option);
+
+            if self.propagate(&mut collapsed, VecDeque::from([cell])) {
+                if let Some(solved) = self.search(collapsed, rng) {
+                    return Some(solved);
+                }
+            }
+        }
+
+        return None;
+    }
+
+    fn propagate(&self, domains: &mut Domains, mut queue: VecDeque<usize>) -> bool {
+        while let Some(cell) = queue.pop_front() {
+            for (side_index, side) in Side::ALL.iter().enumerate() {
+                let Some(neighbour) = self.neighbour(cell, *side) else {
+                    continue;
+                };
+                let mut changed = false;
+
+                for candidate in 0..self.candidates_count {
+                    if !domains[neighbour][candidate] {
+                        continue;
+                    }
+
+                    let supported = (0..self.candidates_count)
+                        .any(|own| domains[cell][own] && self.compatible[side_index][own][candidate]);
+
+                    if !supported {
+                        domains[neighbour][candidate] = false;
+                        changed = true;
+                    }
+                }
+
+                if changed {
+                    if !domains[neighbour].iter().any(|allowed| *allowed) {
+                        return false;
+                    }
+
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        return true;
+    }
+
+    /// Neighbouring cell on the given side, `None` on the borders of grid which isn't wrapped.
+    fn neighbour(&self, cell: usize, side: Side) -> Option<usize> {
+        let row = cell / self.cols;
+        let col = cell % self.cols;
+
+        if self.neighbours.is_some() {
+            let outside = match side {
+                Side::Top => row == 0,
+                Side::Right => col + 1 == self.cols,
+                Side::Bottom => row + 1 == self.rows,
+                Side::Left => col == 0,
+            };
+
+            if outside {
+                return None;
+            }
+        }
+
+        let (row, col) = match side {
+            Side::Top => ((row + self.rows - 1) % self.rows, col),
+            Side::Right => (row, (col + 1) % self.cols),
+            Side::Bottom => ((row + 1) % self.rows, col),
+            Side::Left => (row, (col + self.cols - 1) % self.cols),
+        };
+
+        return Some(flatten_2d_index(row, col, self.cols));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{generator::PatternGenerator, tile::{traits::Tile, edge::Side, triangle::{ElasticTriangleTile, ElasticTileType}}, vec2::Vec2};
+
+    use super::EdgeSolver;
+
+    #[test]
+    fn solved_layout_is_seamless() {
+        let candidates = ElasticTriangleTile::type_a().orientations();
+
+        for seed in 0..8 {
+            let gen_size = Vec2::new(4, 6);
+            let layout = EdgeSolver::new(&candidates, gen_size).solve(&mut StdRng::seed_from_u64(seed)).unwrap();
+            let tiles = layout.into_iter().map(|candidate| candidates[candidate]).collect();
+
+            assert!(PatternGenerator::from_vec(tiles, gen_size, Vec2::new(1, 1)).is_seamless());
+        }
+    }
+
+    #[test]
+    fn layout_matches_fixed_neighbours() {
+        let candidates = ElasticTriangleTile::type_a().orientations();
+        // Odd size has no layout wrapped around the borders
+        let size = Vec2::new(3, 5);
+        let above = vec![Some(0), None, Some(1), Some(2), Some(3)];
+        let left = vec![Some(1), Some(2), None];
+
+        for seed in 0..8 {
+            let layout = EdgeSolver::new(&candidates, size).with_neighbours(above.clone(), left.clone()).solve(&mut StdRng::seed_from_u64(seed)).unwrap();
+            let edges = |row: usize, col: usize| candidates[layout[row * size.y() + col]].edges();
+
+            for (col, candidate) in above.iter().enumerate().filter_map(|(col, candidate)| Some((col, (*candidate)?))) {
+                assert!(candidates[candidate].edges().matches(Side::Bottom, &edges(0, col)));
+            }
+            for (row, candidate) in left.iter().enumerate().filter_map(|(row, candidate)| Some((row, (*candidate)?))) {
+                assert!(candidates[candidate].edges().matches(Side::Right, &edges(row, 0)));
+            }
+            for row in 0..size.x() {
+                for col in 0..size.y() {
+                    assert!(col + 1 == size.y() || edges(row, col).matches(Side::Right, &edges(row, col + 1)));
+                    assert!(row + 1 == size.x() || edges(row, col).matches(Side::Bottom, &edges(row + 1, col)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn impossible_candidates_have_no_layout() {
+        // Filled left side of the tile never matches empty right side of its left neighbour
+        let candidates = [ElasticTriangleTile::new(0.5, ElasticTileType::A)];
+
+        assert_eq!(EdgeSolver::new(&candidates, Vec2::new(2, 2)).solve(&mut StdRng::seed_from_u64(0)), None);
+    }
+
+    #[test]
+    fn search_stops_when_attempts_run_out() {
+        let candidates = ElasticTriangleTile::type_a().orientations();
+        let solver = EdgeSolver { attempts_left: 0, ..EdgeSolver::new(&candidates, Vec2::new(4, 4)) };
+
+        assert_eq!(solver.solve(&mut StdRng::seed_from_u64(0)), None);
+    }
+}
@@ -0,0 +1,83 @@
+use std::io::{self, Write};
+
+use crate::{generator::Generator, truchet_image::TruchetImage, to_postscript::{ToPostScript, PATH_PROCEDURES}, utils::format_number};
+
+/// Writes image as Encapsulated PostScript, `tile_size` is in points.
+///
+/// Procedures of tile types are defined once in the prolog, so every tile is a short call with parameters.
+pub fn write_eps<TGenerator: Generator, W: Write>(image: &TruchetImage<TGenerator>, tile_size: f32, mut writer: W) -> io::Result<()>
+where
+    TGenerator::TileType: ToPostScript
+{
+    let grid_size = image.grid_size();
+    let (width, height) = (grid_size.y() as f32 * tile_size, grid_size.x() as f32 * tile_size);
+
+    let mut procedures = vec![PATH_PROCEDURES];
+    for placed in image.tiles() {
+        let tile_procedures = placed.tile.postscript_procedures();
+        if !tile_procedures.is_empty() && !procedures.contains(&tile_procedures) {
+            procedures.push(tile_procedures);
+        }
+    }
+
+    write!(writer, "%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 {} {}\n", width.ceil(), height.ceil())?;
+    writeln!(writer, "%%HiResBoundingBox: 0 0 {} {}", format_number(width), format_number(height))?;
+    write!(writer, "%%Creator: truchet\n%%EndComments\n%%BeginProlog\n{}\n%%EndProlog\n", procedures.join("\n"))?;
+
+    // Cells are drawn in tile units with `y` axis pointing down as in svg
+    write!(writer, "gsave\n0 {} translate {} {} scale\n", format_number(height), format_number(tile_size), format_number(-tile_size))?;
+    for placed in image.tiles() {
+        writer.write_all(placed.tile.to_postscript(placed.origin()).as_bytes())?;
+    }
+    writer.write_all(b"grestore\nshowpage\n%%EOF\n")?;
+
+    return writer.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{generator::{self, Generator}, image::fixtures::Gradient, to_postscript::ToPostScript, truchet_image::generate, vec2::Vec2};
+
+    use super::write_eps;
+
+    /// Standard PostScript operators the prolog procedures are allowed to use.
+    const OPERATORS: &[&str] = &[
+        "def", "bind", "dict", "begin", "end", "gsave", "grestore", "translate", "rotate", "scale",
+        "exch", "dup", "pop", "add", "sub", "mul", "lt", "if", "moveto", "lineto", "curveto", "closepath",
+        "arc", "arcn", "fill", "showpage", "true", "false"
+    ];
+
+    fn undefined_operators<TGenerator: Generator>(generator: TGenerator) -> HashSet<String> where TGenerator::TileType: ToPostScript {
+        let mut eps = Vec::new();
+        write_eps(&generate(&Gradient(Vec2::new(48, 24)), generator), 10.0, &mut eps).unwrap();
+        let eps = String::from_utf8(eps).unwrap();
+
+        let tokens: Vec<&str> = eps.lines()
+            .filter(|line| !line.starts_with('%'))
+            .flat_map(|line| line.split_whitespace())
+            .flat_map(|token| token.split(['{', '}']))
+            .filter(|token| !token.is_empty() && token.parse::<f32>().is_err())
+            .collect();
+        let defined: HashSet<&str> = tokens.iter().filter_map(|token| token.strip_prefix('/')).collect();
+
+        return tokens.into_iter()
+            .filter(|token| !token.starts_with('/') && !defined.contains(token) && !OPERATORS.contains(token))
+            .map(String::from)
+            .collect();
+    }
+
+    #[test]
+    fn uses_only_defined_and_standard_operators() {
+        let block_size = Vec2::new(6, 6);
+
+        assert!(undefined_operators(generator::fan(block_size)).is_empty());
+        assert!(undefined_operators(generator::circles(block_size)).is_empty());
+        assert!(undefined_operators(generator::waves(block_size)).is_empty());
+        assert!(undefined_operators(generator::calibrated_circles(block_size)).is_empty());
+        assert!(undefined_operators(generator::halftone(block_size)).is_empty());
+        assert!(undefined_operators(generator::knotwork(Vec2::new(2, 2), block_size)).is_empty());
+        assert!(undefined_operators(generator::contours(Vec2::new(2, 2), block_size)).is_empty());
+    }
+}
@@ -1,23 +1,77 @@
-use rand::{prelude::Distribution, distributions::Standard};
-use svg::{node::element::{Group}, Node};
+use std::{f32::consts::PI, ops::Index, sync::Arc};
 
-use crate::{vec2::Vec2, to_svg::ToSVG, utils::flatten_2d_index, tile::{traits::Tile, triangle::ElasticTriangleTile, circle::ElasticCircleTile}};
+use rand::{prelude::Distribution, distributions::Standard, seq::SliceRandom, Rng};
+
+use crate::{vec2::Vec2, image::BlockSample, dither::Dithering, utils::flatten_2d_index, edge_solver::EdgeSolver, tile::{traits::Tile, triangle::{ElasticTriangleTile, ElasticTileType}, circle::ElasticCircleTile, edge::Side, transfer::{TransferCurve, TransferTile}, mapped::MappedTile, knot::{KnotTile, KnotTileType}, contour::ContourTile, dot::DotTile, dynamic::AnyTile, placed::PlacedTile}};
 
 pub trait Generator {
     type TileType: Tile;
 
-    fn clone_with_brightness(&self, brightness: f32) -> Self;
     fn generator_block_size(&self) -> Vec2<usize>;
     fn source_image_block_size(&self) -> Vec2<usize>;
+
+    /// Tile laid out at the absolute row and column of the image, `block` is the sampled source image block the tile lies in.
+    ///
+    /// Images keep only laid out tiles, so layouts don't have to repeat with generator blocks.
+    fn layout(&self, row: usize, col: usize, block: &BlockSample) -> Self::TileType where Self: Sized;
+
+    /// Tile laid out from the sample of its own cell, see [`crate::image::Sampling::Tile`].
+    ///
+    /// By default the sample is treated the same way as the sample of the whole block.
+    fn layout_tile(&self, row: usize, col: usize, sample: &BlockSample) -> Self::TileType where Self: Sized {
+        return self.layout(row, col, sample);
+    }
+
+    /// Called once before the tiles of image of `grid_size` tile rows and columns are laid out,
+    /// generators which lay out the whole image at once do it here.
+    fn prepare(&mut self, _grid_size: Vec2<usize>) {}
+
+    /// Brightness levels which generator reproduces, `None` for generators with continuous tone.
+    fn brightness_levels(&self) -> Option<Vec<f32>> {
+        return None;
+    }
+
+    /// Method of distributing quantization error between blocks of generators with discrete brightness levels.
+    fn dithering(&self) -> Dithering {
+        return Dithering::default();
+    }
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "PatternGeneratorData<TTile>", bound(serialize = "TTile: serde::Serialize", deserialize = "TTile: Tile + serde::Deserialize<'de>")))]
 pub struct PatternGenerator<TTile: Tile> {
     gen_size: Vec2<usize>,
     src_img_block_size: Vec2<usize>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::tile::template::serialize_tiles"))]
+    tiles: Vec<TTile>
+}
+
+/// Deserialized pattern before its number of tiles is checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(bound(deserialize = "TTile: serde::Deserialize<'de>"))]
+struct PatternGeneratorData<TTile> {
+    gen_size: Vec2<usize>,
+    src_img_block_size: Vec2<usize>,
+    #[serde(deserialize_with = "crate::tile::template::deserialize_tiles")]
     tiles: Vec<TTile>
 }
 
+#[cfg(feature = "serde")]
+impl<TTile: Tile> TryFrom<PatternGeneratorData<TTile>> for PatternGenerator<TTile> {
+    type Error = String;
+
+    fn try_from(data: PatternGeneratorData<TTile>) -> Result<Self, Self::Error> {
+        let expected = data.gen_size.x() * data.gen_size.y();
+        if data.tiles.len() != expected {
+            return Err(format!("pattern of size {}x{} needs {} tiles, got {}", data.gen_size.x(), data.gen_size.y(), expected, data.tiles.len()));
+        }
+
+        return Ok(Self { gen_size: data.gen_size, src_img_block_size: data.src_img_block_size, tiles: data.tiles });
+    }
+}
+
 
 impl<TTile: Tile> PatternGenerator<TTile> {
     pub fn from_slice<const GEN_X_SIZE: usize, const GEN_Y_SIZE: usize>(tiles: [[TTile; GEN_Y_SIZE]; GEN_X_SIZE], src_img_block_size: Vec2<usize>) -> Self {
@@ -33,7 +87,7 @@ impl<TTile: Tile> PatternGenerator<TTile> {
          };
     }
 
-    pub fn from_vec(mut tiles: Vec<TTile>, gen_size: Vec2<usize>, src_img_block_size: Vec2<usize>) -> Self {
+    pub fn from_vec(mut tiles: Vec<TTile>, gen_size: Vec2<usize>, src_img_block_size: Vec2<usize>) -> Self where TTile: Default {
         let missing_tiles_count = gen_size.x() * gen_size.y() - tiles.len();
 
         if  missing_tiles_count > 0 {
@@ -48,20 +102,66 @@ impl<TTile: Tile> PatternGenerator<TTile> {
             src_img_block_size
          };
     }
-}
 
-impl<TTile: Tile> Generator for PatternGenerator<TTile> {
-    type TileType = TTile;
+    /// Passes brightness of every tile through the transfer curve.
+    pub fn with_transfer_curve(self, curve: TransferCurve) -> PatternGenerator<TransferTile<TTile>> {
+        let curve = Arc::new(curve);
+
+        return PatternGenerator {
+            gen_size: self.gen_size,
+            src_img_block_size: self.src_img_block_size,
+            tiles: self.tiles.into_iter().map(|tile| TransferTile::new(tile, curve.clone())).collect()
+        };
+    }
+
+    /// Checks whether motif of every tile continues into its neighbours when pattern is repeated.
+    pub fn is_seamless(&self) -> bool {
+        let rows = self.gen_size.x();
+        let cols = self.gen_size.y();
+
+        for row in 0..rows {
+            for col in 0..cols {
+                let edges = self.tiles[flatten_2d_index(row, col, cols)].edges();
+                let right = self.tiles[flatten_2d_index(row, (col + 1) % cols, cols)].edges();
+                let bottom = self.tiles[flatten_2d_index((row + 1) % rows, col, cols)].edges();
+
+                if !edges.matches(Side::Right, &right) || !edges.matches(Side::Bottom, &bottom) {
+                    return false;
+                }
+            }
+        }
+
+        return true;
+    }
 
-    fn clone_with_brightness(&self, brightness: f32) -> Self {
-        let mut clone = self.clone();
- 
-        for tile in &mut clone.tiles  {
-            tile.set_brightness(brightness);
+    /// Tile at the given row and column of the pattern, `None` if it is out of the pattern.
+    pub fn get(&self, row: usize, col: usize) -> Option<&TTile> {
+        if row >= self.gen_size.x() || col >= self.gen_size.y() {
+            return None;
         }
 
-        return clone;
+        return self.tiles.get(flatten_2d_index(row, col, self.gen_size.y()));
+    }
+
+    /// Tiles of the pattern row by row.
+    pub fn tiles(&self) -> impl Iterator<Item = PlacedTile<'_, TTile>> {
+        let cols = self.gen_size.y();
+        return self.tiles.iter().enumerate().map(move |(i, tile)| PlacedTile::new(i / cols, i % cols, tile));
+    }
+}
+
+impl<TTile: Tile> Index<(usize, usize)> for PatternGenerator<TTile> {
+    type Output = TTile;
+
+    /// Tile at row and column, panics if it is out of the pattern.
+    #[inline]
+    fn index(&self, (row, col): (usize, usize)) -> &TTile {
+        return self.get(row, col).expect("Tile position is out of the pattern");
     }
+}
+
+impl<TTile: Tile> Generator for PatternGenerator<TTile> {
+    type TileType = TTile;
 
     #[inline]
     fn generator_block_size(&self) -> Vec2<usize> {
@@ -72,28 +172,17 @@ impl<TTile: Tile> Generator for PatternGenerator<TTile> {
     fn source_image_block_size(&self) -> Vec2<usize> {
         return self.src_img_block_size;
     }
-}
 
-impl<TTile: Tile + ToSVG> ToSVG for PatternGenerator<TTile> {
-    fn to_svg_node(&self) -> Box<dyn Node> {
-        let mut g = Group::new();
-
-        for tile_x in 0..self.gen_size.x() {
-            for tile_y in 0..self.gen_size.y() {
-                let tile_origin = Vec2::new(tile_y as f32, tile_x as f32);
-                let tile = self.tiles[flatten_2d_index(tile_x, tile_y, self.gen_size.y())].to_svg_node();
-                let tile_translated = Group::new()
-                    .set("transform", format!("translate({} {})", tile_origin.x(), tile_origin.y()))
-                    .add(tile);
-                g.append(tile_translated);
-            }
-        }
-
-        return Box::new(g);
+    /// Pattern is repeated over the whole image.
+    fn layout(&self, row: usize, col: usize, block: &BlockSample) -> TTile {
+        let mut tile = self[(row % self.gen_size.x(), col % self.gen_size.y())].clone();
+        tile.configure(block);
+        return tile;
     }
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RandomGenerator<TTile: Tile>(PatternGenerator<TTile>);
 
 impl<TTile> RandomGenerator<TTile>
@@ -104,7 +193,7 @@ where
     fn new(gen_size: Vec2<usize>, src_img_block_size: Vec2<usize>) -> Self {
         let tiles_count = gen_size.x() * gen_size.y();
         let tiles: Vec<TTile> = (0..tiles_count).map(|_| rand::random()).collect();
-        return Self(PatternGenerator::from_vec(tiles, gen_size, src_img_block_size));
+        return Self(PatternGenerator { gen_size, src_img_block_size, tiles });
     }
 }
 
@@ -115,29 +204,362 @@ where
 {
     type TileType = TTile;
 
-    fn clone_with_brightness(&self, brightness: f32) -> Self {
-        let mut clone = Self::new(self.generator_block_size(), self.source_image_block_size());
+    fn layout(&self, _row: usize, _col: usize, block: &BlockSample) -> TTile {
+        let mut tile: TTile = rand::random();
+        tile.configure(block);
+        return tile;
+    }
+
+    fn generator_block_size(&self) -> Vec2<usize> {
+        return self.0.generator_block_size();
+    }
+
+    fn source_image_block_size(&self) -> Vec2<usize> {
+        return self.0.source_image_block_size();
+    }
+}
+
+/// Generator which randomly lays out tiles so that neighbours match along shared edges.
+///
+/// Images are solved block by block, tiles of every block match the blocks already placed above and to the left of it,
+/// so the layout doesn't repeat with generator blocks. Pattern wrapped around its borders is solved as well
+/// and repeated instead when some block has no valid layout.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConstrainedGenerator<TTile: Tile> {
+    pattern: PatternGenerator<TTile>,
+    candidates: Vec<TTile>,
+    // Candidate of every tile of the prepared image with its number of tile columns
+    #[cfg_attr(feature = "serde", serde(skip))]
+    layout: Option<(Vec<usize>, usize)>
+}
+
+impl<TTile: Tile> ConstrainedGenerator<TTile> {
+    /// Returns `None` if candidate tiles can't be laid out seamlessly on grid of the given size.
+    pub fn new(candidates: &[TTile], gen_size: Vec2<usize>, src_img_block_size: Vec2<usize>) -> Option<Self> {
+        let layout = EdgeSolver::new(candidates, gen_size).solve(&mut rand::thread_rng())?;
+        let tiles = layout.into_iter().map(|candidate| candidates[candidate].clone()).collect();
+
+        return Some(Self {
+            pattern: PatternGenerator { gen_size, src_img_block_size, tiles },
+            candidates: candidates.to_vec(),
+            layout: None
+        });
+    }
+
+    /// Pattern wrapped around its borders, used when image can't be solved block by block.
+    #[inline]
+    pub fn pattern(&self) -> &PatternGenerator<TTile> {
+        return &self.pattern;
+    }
 
-        for tile in &mut clone.0.tiles {
-            tile.set_brightness(brightness);
+    /// Candidate of every tile of the grid row by row, `None` if some block has no valid layout.
+    fn solve_blocks<R: Rng + ?Sized>(&self, grid_size: Vec2<usize>, rng: &mut R) -> Option<Vec<usize>> {
+        let gen_size = self.pattern.gen_size;
+        let cols = grid_size.y();
+        let mut layout = vec![0; grid_size.x() * cols];
+
+        for block_row in (0..grid_size.x()).step_by(gen_size.x().max(1)) {
+            for block_col in (0..cols).step_by(gen_size.y().max(1)) {
+                let size = Vec2::new(gen_size.x().min(grid_size.x() - block_row), gen_size.y().min(cols - block_col));
+                let above = (0..size.y())
+                    .map(|col| block_row.checked_sub(1).map(|row| layout[flatten_2d_index(row, block_col + col, cols)]))
+                    .collect();
+                let left = (0..size.x())
+                    .map(|row| block_col.checked_sub(1).map(|col| layout[flatten_2d_index(block_row + row, col, cols)]))
+                    .collect();
+
+                let block = EdgeSolver::new(&self.candidates, size).with_neighbours(above, left).solve(rng)?;
+                for (index, candidate) in block.into_iter().enumerate() {
+                    layout[flatten_2d_index(block_row + index / size.y(), block_col + index % size.y(), cols)] = candidate;
+                }
+            }
         }
 
-        return clone;
+        return Some(layout);
+    }
+}
+
+impl<TTile: Tile> Generator for ConstrainedGenerator<TTile> {
+    type TileType = TTile;
+
+    fn prepare(&mut self, grid_size: Vec2<usize>) {
+        let layout = self.solve_blocks(grid_size, &mut rand::thread_rng());
+        self.layout = layout.map(|layout| (layout, grid_size.y()));
     }
 
+    fn layout(&self, row: usize, col: usize, block: &BlockSample) -> TTile {
+        let candidate = self.layout.as_ref()
+            .filter(|(_, cols)| col < *cols)
+            .and_then(|(layout, cols)| layout.get(flatten_2d_index(row, col, *cols)));
+
+        let Some(candidate) = candidate else {
+            return self.pattern.layout(row, col, block);
+        };
+
+        let mut tile = self.candidates[*candidate].clone();
+        tile.configure(block);
+        return tile;
+    }
+
+    #[inline]
     fn generator_block_size(&self) -> Vec2<usize> {
-        return self.0.generator_block_size();
+        return self.pattern.generator_block_size();
     }
 
+    #[inline]
     fn source_image_block_size(&self) -> Vec2<usize> {
-        return self.0.source_image_block_size();
+        return self.pattern.source_image_block_size();
+    }
+}
+
+/// Generator which rotates tiles of the pattern so that their motif follows edges of the source image.
+///
+/// Blocks with coherence below threshold have no distinct edges and keep the original pattern.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientGenerator<TTile: Tile> {
+    pattern: PatternGenerator<TTile>,
+    coherence_threshold: f32
+}
+
+impl<TTile: Tile> GradientGenerator<TTile> {
+    pub fn new(pattern: PatternGenerator<TTile>, coherence_threshold: f32) -> Self {
+        return Self { pattern, coherence_threshold };
+    }
+
+    fn aligned_tile(tile: &TTile, edge_direction: f32) -> TTile {
+        let deviation = |candidate: &TTile| {
+            let Some(direction) = candidate.direction() else {
+                return f32::INFINITY;
+            };
+            let diff = (direction - edge_direction).rem_euclid(PI);
+            return diff.min(PI - diff);
+        };
+
+        // Prefer first orientation on ties so the least transformed tile is taken
+        return tile.orientations()
+            .into_iter()
+            .reduce(|best, candidate| if deviation(&candidate) < deviation(&best) { candidate } else { best })
+            .unwrap_or_else(|| tile.clone());
+    }
+}
+
+impl<TTile: Tile> Generator for GradientGenerator<TTile> {
+    type TileType = TTile;
+
+    #[inline]
+    fn generator_block_size(&self) -> Vec2<usize> {
+        return self.pattern.generator_block_size();
+    }
+
+    #[inline]
+    fn source_image_block_size(&self) -> Vec2<usize> {
+        return self.pattern.source_image_block_size();
+    }
+
+    fn layout(&self, row: usize, col: usize, block: &BlockSample) -> TTile {
+        let tile = self.pattern.layout(row, col, block);
+
+        if block.coherence() >= self.coherence_threshold {
+            return Self::aligned_tile(&tile, block.edge_direction());
+        }
+
+        return tile;
+    }
+}
+
+/// Generator which treats tiles as a palette of fixed tones and picks single tile per source image block.
+///
+/// Tiles are used as is, their brightness is never changed. Quantization error is diffused
+/// between neighbouring blocks, so discrete tile sets can reproduce smooth tone.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DitherGenerator<TTile: Tile> {
+    // Sorted by brightness
+    palette: Vec<(TTile, f32)>,
+    src_img_block_size: Vec2<usize>,
+    dithering: Dithering
+}
+
+impl<TTile: Tile> DitherGenerator<TTile> {
+    /// Palette contains tiles with brightness they render at, palette must not be empty.
+    pub fn new(mut palette: Vec<(TTile, f32)>, src_img_block_size: Vec2<usize>, dithering: Dithering) -> Self {
+        assert!(!palette.is_empty(), "Palette must contain at least one tile");
+        palette.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        return Self {
+            palette,
+            src_img_block_size,
+            dithering
+        };
+    }
+
+    /// Creates palette from tiles, brightness of every tile is derived from its coverage.
+    pub fn from_tiles(tiles: Vec<TTile>, src_img_block_size: Vec2<usize>, dithering: Dithering) -> Self {
+        let palette = tiles.into_iter()
+            .map(|tile| {
+                let brightness = 1.0 - tile.coverage();
+                return (tile, brightness);
+            })
+            .collect();
+
+        return Self::new(palette, src_img_block_size, dithering);
+    }
+
+    fn nearest_tile(&self, brightness: f32) -> TTile {
+        let distance = |(_, level): &&(TTile, f32)| (level - brightness).abs();
+        let min_distance = self.palette.iter()
+            .map(|entry| distance(&entry))
+            .fold(f32::INFINITY, f32::min);
+
+        // Tiles with the same tone are picked randomly, e.g. different orientations of the same tile
+        let nearest: Vec<_> = self.palette.iter()
+            .filter(|entry| distance(entry) <= min_distance)
+            .collect();
+
+        return nearest.choose(&mut rand::thread_rng()).map_or_else(|| self.palette[0].0.clone(), |(tile, _)| tile.clone());
+    }
+}
+
+impl<TTile: Tile> Generator for DitherGenerator<TTile> {
+    type TileType = TTile;
+
+    #[inline]
+    fn layout(&self, _row: usize, _col: usize, block: &BlockSample) -> TTile {
+        return self.nearest_tile(block.brightness());
+    }
+
+    #[inline]
+    fn generator_block_size(&self) -> Vec2<usize> {
+        return Vec2::new(1, 1);
+    }
+
+    #[inline]
+    fn source_image_block_size(&self) -> Vec2<usize> {
+        return self.src_img_block_size;
+    }
+
+    fn brightness_levels(&self) -> Option<Vec<f32>> {
+        return Some(self.palette.iter().map(|(_, brightness)| *brightness).collect());
+    }
+
+    #[inline]
+    fn dithering(&self) -> Dithering {
+        return self.dithering;
+    }
+}
+
+/// Generator of random alternating knotwork made of crossings and arcs.
+///
+/// Crossings alternate by the absolute position of the tile, so they alternate across generator blocks as well.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KnotGenerator {
+    gen_size: Vec2<usize>,
+    src_img_block_size: Vec2<usize>,
+    crossing_probability: f32
+}
+
+impl KnotGenerator {
+    pub fn new(gen_size: Vec2<usize>, src_img_block_size: Vec2<usize>, crossing_probability: f32) -> Self {
+        return Self { gen_size, src_img_block_size, crossing_probability: crossing_probability.clamp(0.0, 1.0) };
+    }
+
+    fn random_tile<R: Rng>(&self, rng: &mut R, row: usize, col: usize) -> KnotTile {
+        let tile_type = if rng.gen_bool(self.crossing_probability as f64) {
+            KnotTileType::Crossing { horizontal_over: true }
+        } else {
+            KnotTileType::Arcs { flipped: rng.gen() }
+        };
+
+        let mut tile = KnotTile::new(tile_type, 0.2, 0.05);
+        tile.alternate(row, col);
+        return tile;
+    }
+}
+
+impl Generator for KnotGenerator {
+    type TileType = KnotTile;
+
+    /// Crossings alternate by the absolute position, so strands alternate across the whole image.
+    fn layout(&self, row: usize, col: usize, block: &BlockSample) -> KnotTile {
+        let mut tile = self.random_tile(&mut rand::thread_rng(), row, col);
+        tile.configure(block);
+        return tile;
+    }
+
+    #[inline]
+    fn generator_block_size(&self) -> Vec2<usize> {
+        return self.gen_size;
+    }
+
+    #[inline]
+    fn source_image_block_size(&self) -> Vec2<usize> {
+        return self.src_img_block_size;
+    }
+}
+
+/// Generator of contour map tiles, isolines follow brightness of the block corners.
+///
+/// Corners of tiles inside of the block are interpolated bilinearly, so isolines stay continuous across tiles and blocks.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContourGenerator {
+    pattern: PatternGenerator<ContourTile>
+}
+
+impl ContourGenerator {
+    pub fn new(gen_size: Vec2<usize>, src_img_block_size: Vec2<usize>, thresholds: &[f32], width: f32) -> Self {
+        let pattern = PatternGenerator {
+            gen_size,
+            src_img_block_size,
+            tiles: vec![ContourTile::new(thresholds, width); gen_size.x() * gen_size.y()]
+        };
+
+        return Self { pattern };
+    }
+
+    /// Corners of the tile at the row and column of the block interpolated bilinearly between the block corners.
+    fn tile_corners(&self, block: &BlockSample, row: usize, col: usize) -> [f32; 4] {
+        let [top_left, top_right, bottom_right, bottom_left] = block.corners();
+        let (rows, cols) = (self.pattern.gen_size.x(), self.pattern.gen_size.y());
+        let bilinear = |row: usize, col: usize| {
+            let (u, v) = (col as f32 / cols as f32, row as f32 / rows as f32);
+            let top = top_left + (top_right - top_left) * u;
+            let bottom = bottom_left + (bottom_right - bottom_left) * u;
+            return top + (bottom - top) * v;
+        };
+
+        return [bilinear(row, col), bilinear(row, col + 1), bilinear(row + 1, col + 1), bilinear(row + 1, col)];
     }
 }
 
-impl<TTile: Tile + ToSVG> ToSVG for RandomGenerator<TTile> {
+impl Generator for ContourGenerator {
+    type TileType = ContourTile;
+
+    fn layout(&self, row: usize, col: usize, block: &BlockSample) -> ContourTile {
+        let (row, col) = (row % self.pattern.gen_size.x(), col % self.pattern.gen_size.y());
+        let mut tile = self.pattern[(row, col)].clone();
+        tile.set_corners(self.tile_corners(block, row, col));
+        return tile;
+    }
+
+    /// Corners of the tile are sampled directly instead of being interpolated.
+    fn layout_tile(&self, row: usize, col: usize, sample: &BlockSample) -> ContourTile {
+        let mut tile = self.pattern[(row % self.pattern.gen_size.x(), col % self.pattern.gen_size.y())].clone();
+        tile.set_corners(sample.corners());
+        return tile;
+    }
+
+    #[inline]
+    fn generator_block_size(&self) -> Vec2<usize> {
+        return self.pattern.generator_block_size();
+    }
+
     #[inline]
-    fn to_svg_node(&self) -> Box<dyn Node> {
-        return self.0.to_svg_node();
+    fn source_image_block_size(&self) -> Vec2<usize> {
+        return self.pattern.source_image_block_size();
     }
 }
 
@@ -183,7 +605,132 @@ pub fn waves(image_block_size: Vec2<usize>) -> PatternGenerator<ElasticCircleTil
     ], image_block_size);
 }
 
+/// Amplitude modulated halftone with screen rotated by 45 degrees.
+pub fn halftone(image_block_size: Vec2<usize>) -> PatternGenerator<DotTile> {
+    return PatternGenerator::from_slice([[DotTile::halftone(1, 1)]], image_block_size);
+}
+
+pub fn stippled(image_block_size: Vec2<usize>) -> PatternGenerator<DotTile> {
+    return PatternGenerator::from_slice([[DotTile::stipple(0.08)]], image_block_size);
+}
+
+/// Circles pattern where every other tile is replaced by a halftone dot.
+pub fn dotted_circles(image_block_size: Vec2<usize>) -> PatternGenerator<AnyTile> {
+    return PatternGenerator::from_slice([
+        [AnyTile::new(ElasticCircleTile::default()), AnyTile::new(DotTile::default())],
+        [AnyTile::new(DotTile::default()), AnyTile::new(ElasticCircleTile::default())]
+    ], image_block_size);
+}
+
+/// Fan pattern with tiles turned along edges of the source image.
+pub fn edge_traced(image_block_size: Vec2<usize>) -> GradientGenerator<ElasticTriangleTile> {
+    return GradientGenerator::new(fan(image_block_size), 0.5);
+}
+
+/// Triangle tiles in every orientation with 3 fixed tones picked by Floyd-Steinberg dithering.
+pub fn dithered(image_block_size: Vec2<usize>) -> DitherGenerator<ElasticTriangleTile> {
+    let palette = [(0.0, 0.25), (0.5, 0.5), (1.0, 0.75)]
+        .into_iter()
+        .flat_map(|(t, brightness)| {
+            ElasticTriangleTile::new(t, ElasticTileType::A)
+                .orientations()
+                .into_iter()
+                .map(move |tile| (tile, brightness))
+        })
+        .collect();
+
+    return DitherGenerator::new(palette, image_block_size, Dithering::FloydSteinberg);
+}
+
+/// Circles pattern calibrated so that rendered tone matches source brightness where possible.
+pub fn calibrated_circles(image_block_size: Vec2<usize>) -> PatternGenerator<TransferTile<ElasticCircleTile>> {
+    let curve = TransferCurve::calibrate(&ElasticCircleTile::default(), Tile::coverage, 64);
+    return circles(image_block_size).with_transfer_curve(curve);
+}
+
+/// Circles pattern with band width following brightness and radius following local contrast.
+pub fn contrast_circles(image_block_size: Vec2<usize>) -> PatternGenerator<MappedTile<ElasticCircleTile>> {
+    let tile = |flipped: bool| MappedTile::new(ElasticCircleTile::new(flipped, 0.1, 0.5), |tile, block| {
+        tile.set_width((1.0 - block.brightness()) / 2.0);
+        tile.set_radius(0.5 - block.contrast() / 4.0);
+    });
+
+    return PatternGenerator::from_slice([
+        [tile(false), tile(true)],
+        [tile(true), tile(false)]
+    ], image_block_size);
+}
+
+/// Random knotwork where half of the tiles are crossings.
+pub fn knotwork(gen_size: Vec2<usize>, src_img_block_size: Vec2<usize>) -> KnotGenerator {
+    return KnotGenerator::new(gen_size, src_img_block_size, 0.5);
+}
+
+/// Contour map with isolines at four brightness levels.
+pub fn contours(gen_size: Vec2<usize>, src_img_block_size: Vec2<usize>) -> ContourGenerator {
+    return ContourGenerator::new(gen_size, src_img_block_size, &[0.2, 0.4, 0.6, 0.8], 0.06);
+}
+
 pub fn random(gen_size: Vec2<usize>, src_img_block_size: Vec2<usize>) -> RandomGenerator<ElasticTriangleTile> {
     return RandomGenerator::new(gen_size, src_img_block_size);
 }
 
+/// Random triangle tiles with continuous colour regions, generator size must be even in both directions.
+pub fn seamless_random(gen_size: Vec2<usize>, src_img_block_size: Vec2<usize>) -> Option<ConstrainedGenerator<ElasticTriangleTile>> {
+    return ConstrainedGenerator::new(&ElasticTriangleTile::type_a().orientations(), gen_size, src_img_block_size);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{image::fixtures::Uniform, tile::{traits::Tile, edge::Side}, truchet_image::generate, vec2::Vec2};
+
+    use super::seamless_random;
+
+    #[test]
+    fn seamless_random_layout_is_seamless_and_not_periodic() {
+        let gen_size = Vec2::new(4, 4);
+        let image = generate(&Uniform(Vec2::new(12, 12), 0.5), seamless_random(gen_size, Vec2::new(2, 2)).unwrap());
+        let grid_size = image.grid_size();
+        let edges = |row: usize, col: usize| image.get(row, col).unwrap().edges();
+
+        for row in 0..grid_size.x() {
+            for col in 0..grid_size.y() {
+                assert!(col + 1 == grid_size.y() || edges(row, col).matches(Side::Right, &edges(row, col + 1)));
+                assert!(row + 1 == grid_size.x() || edges(row, col).matches(Side::Bottom, &edges(row + 1, col)));
+            }
+        }
+
+        let repeats = |offset: Vec2<usize>| (0..grid_size.x() - offset.x())
+            .all(|row| (0..grid_size.y() - offset.y()).all(|col| edges(row, col) == edges(row + offset.x(), col + offset.y())));
+        assert!(!repeats(Vec2::new(gen_size.x(), 0)));
+        assert!(!repeats(Vec2::new(0, gen_size.y())));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pattern_round_trips_through_json() {
+        use crate::{to_svg::ToSVG, tile::circle::ElasticCircleTile};
+        use super::{circles, Generator, PatternGenerator};
+
+        let pattern = circles(Vec2::new(4, 6));
+        let restored: PatternGenerator<ElasticCircleTile> = serde_json::from_str(&serde_json::to_string(&pattern).unwrap()).unwrap();
+
+        assert_eq!(restored.generator_block_size(), pattern.generator_block_size());
+        assert_eq!(restored.source_image_block_size(), pattern.source_image_block_size());
+        let svg = |pattern: &PatternGenerator<ElasticCircleTile>| pattern.tiles().map(|placed| placed.to_svg_node().to_string()).collect::<Vec<_>>();
+        assert_eq!(svg(&restored), svg(&pattern));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pattern_with_wrong_tiles_count_is_rejected() {
+        use crate::tile::circle::ElasticCircleTile;
+        use super::{circles, PatternGenerator};
+
+        let mut value = serde_json::to_value(circles(Vec2::new(4, 6))).unwrap();
+        value["tiles"].as_array_mut().unwrap().pop();
+
+        let error = serde_json::from_value::<PatternGenerator<ElasticCircleTile>>(value).err().unwrap();
+        assert!(error.to_string().contains("needs 4 tiles, got 3"), "{}", error);
+    }
+}
@@ -0,0 +1,483 @@
+use std::f32::consts::PI;
+
+use svg::node::element::path::{Command, Data, Position};
+
+use crate::{vec2::Vec2, tile::symmetry::Orientation};
+
+/// Number of straight pieces every curve is split into when outline is flattened.
+const CURVE_PIECES: usize = 32;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Segment {
+    MoveTo(Vec2<f32>),
+    LineTo(Vec2<f32>),
+    /// Elliptical arc with the same meaning of parameters as svg arc command, rotation is in degrees.
+    ArcTo {
+        radii: Vec2<f32>,
+        x_rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        end: Vec2<f32>
+    },
+    CubicTo(Vec2<f32>, Vec2<f32>, Vec2<f32>),
+    Close
+}
+
+/// Arc in center parameterization, angles are in radians.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CenterArc {
+    pub center: Vec2<f32>,
+    pub radii: Vec2<f32>,
+    pub x_rotation: f32,
+    pub start_angle: f32,
+    pub sweep_angle: f32
+}
+
+impl CenterArc {
+    /// Converts svg arc from `start` to `end` to center parameterization.
+    pub fn from_endpoints(start: Vec2<f32>, end: Vec2<f32>, radii: Vec2<f32>, x_rotation: f32, large_arc: bool, sweep: bool) -> Self {
+        let phi = x_rotation.to_radians();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let (mut rx, mut ry) = (radii.x().abs(), radii.y().abs());
+
+        let dx = (start.x() - end.x()) / 2.0;
+        let dy = (start.y() - end.y()) / 2.0;
+        let x1 = cos_phi * dx + sin_phi * dy;
+        let y1 = -sin_phi * dx + cos_phi * dy;
+
+        // Radii are scaled up if they are too small to connect the points
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coef = if denominator > 0.0 { (numerator / denominator).max(0.0).sqrt() } else { 0.0 };
+        if large_arc == sweep {
+            coef = -coef;
+        }
+
+        let cx1 = coef * rx * y1 / ry;
+        let cy1 = -coef * ry * x1 / rx;
+        let center = Vec2::new(
+            cos_phi * cx1 - sin_phi * cy1 + (start.x() + end.x()) / 2.0,
+            sin_phi * cx1 + cos_phi * cy1 + (start.y() + end.y()) / 2.0
+        );
+
+        let start_angle = f32::atan2((y1 - cy1) / ry, (x1 - cx1) / rx);
+        let end_angle = f32::atan2((-y1 - cy1) / ry, (-x1 - cx1) / rx);
+        let mut sweep_angle = (end_angle - start_angle).rem_euclid(2.0 * PI);
+        if !sweep && sweep_angle > 0.0 {
+            sweep_angle -= 2.0 * PI;
+        }
+
+        return Self { center, radii: Vec2::new(rx, ry), x_rotation: phi, start_angle, sweep_angle };
+    }
+
+    pub fn point(&self, angle: f32) -> Vec2<f32> {
+        let (sin_phi, cos_phi) = self.x_rotation.sin_cos();
+        let (sin, cos) = angle.sin_cos();
+
+        return Vec2::new(
+            self.center.x() + self.radii.x() * cos_phi * cos - self.radii.y() * sin_phi * sin,
+            self.center.y() + self.radii.x() * sin_phi * cos + self.radii.y() * cos_phi * sin
+        );
+    }
+
+    /// Approximates arc with cubic Bézier curves spanning at most a quarter turn each,
+    /// curves are given as control points and end point.
+    pub fn to_cubics(&self) -> Vec<[Vec2<f32>; 3]> {
+        let (sin_phi, cos_phi) = self.x_rotation.sin_cos();
+        let derivative = |angle: f32| {
+            let (sin, cos) = angle.sin_cos();
+            return Vec2::new(
+                -self.radii.x() * cos_phi * sin - self.radii.y() * sin_phi * cos,
+                -self.radii.x() * sin_phi * sin + self.radii.y() * cos_phi * cos
+            );
+        };
+
+        let pieces = (self.sweep_angle.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+        let step = self.sweep_angle / pieces as f32;
+        let k = 4.0 / 3.0 * (step / 4.0).tan();
+
+        return (0..pieces)
+            .map(|i| {
+                let from = self.start_angle + step * i as f32;
+                let to = from + step;
+                return [
+                    self.point(from) + derivative(from) * k,
+                    self.point(to) - derivative(to) * k,
+                    self.point(to)
+                ];
+            })
+            .collect();
+    }
+}
+
+/// Filled shape made of one or more closed subpaths, filled with nonzero rule as svg path.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Outline {
+    segments: Vec<Segment>
+}
+
+impl Outline {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    pub fn from_segments(segments: Vec<Segment>) -> Self {
+        return Self { segments };
+    }
+
+    #[inline]
+    pub fn segments(&self) -> &[Segment] {
+        return &self.segments;
+    }
+
+    pub fn move_to(mut self, point: Vec2<f32>) -> Self {
+        self.segments.push(Segment::MoveTo(point));
+        return self;
+    }
+
+    pub fn line_to(mut self, point: Vec2<f32>) -> Self {
+        self.segments.push(Segment::LineTo(point));
+        return self;
+    }
+
+    pub fn arc_to(mut self, radius: f32, large_arc: bool, sweep: bool, end: Vec2<f32>) -> Self {
+        self.segments.push(Segment::ArcTo { radii: Vec2::new(radius, radius), x_rotation: 0.0, large_arc, sweep, end });
+        return self;
+    }
+
+    pub fn cubic_to(mut self, control1: Vec2<f32>, control2: Vec2<f32>, end: Vec2<f32>) -> Self {
+        self.segments.push(Segment::CubicTo(control1, control2, end));
+        return self;
+    }
+
+    pub fn close(mut self) -> Self {
+        self.segments.push(Segment::Close);
+        return self;
+    }
+
+    /// Outline transformed within the unit cell.
+    pub fn oriented(&self, orientation: Orientation) -> Self {
+        let point = |p: Vec2<f32>| -> Vec2<f32> { orientation.apply(p.into()).into() };
+        let segments = self.segments.iter()
+            .map(|segment| match *segment {
+                Segment::MoveTo(p) => Segment::MoveTo(point(p)),
+                Segment::LineTo(p) => Segment::LineTo(point(p)),
+                Segment::ArcTo { radii, x_rotation, large_arc, sweep, end } => {
+                    let x_rotation = if orientation.mirrored() { -x_rotation } else { x_rotation };
+                    Segment::ArcTo {
+                        radii,
+                        x_rotation: x_rotation + 90.0 * orientation.quarter_turns() as f32,
+                        large_arc,
+                        sweep: sweep != orientation.mirrored(),
+                        end: point(end)
+                    }
+                },
+                Segment::CubicTo(c1, c2, end) => Segment::CubicTo(point(c1), point(c2), point(end)),
+                Segment::Close => Segment::Close,
+            })
+            .collect();
+
+        return Self { segments };
+    }
+
+    /// Outline scaled and then moved by offset.
+    pub fn transformed(&self, scale: f32, offset: Vec2<f32>) -> Self {
+        let point = |p: Vec2<f32>| p * scale + offset;
+        let segments = self.segments.iter()
+            .map(|segment| match *segment {
+                Segment::MoveTo(p) => Segment::MoveTo(point(p)),
+                Segment::LineTo(p) => Segment::LineTo(point(p)),
+                Segment::ArcTo { radii, x_rotation, large_arc, sweep, end } => Segment::ArcTo {
+                    radii: radii * scale, x_rotation, large_arc, sweep, end: point(end)
+                },
+                Segment::CubicTo(c1, c2, end) => Segment::CubicTo(point(c1), point(c2), point(end)),
+                Segment::Close => Segment::Close,
+            })
+            .collect();
+
+        return Self { segments };
+    }
+
+    /// Same outline with arcs replaced by cubic Bézier curves, for formats without elliptical arcs.
+    pub fn with_cubic_arcs(&self) -> Self {
+        let mut segments = Vec::with_capacity(self.segments.len());
+        let mut position = Vec2::new(0.0, 0.0);
+        let mut subpath_start = position;
+
+        for segment in &self.segments {
+            match *segment {
+                Segment::MoveTo(p) => {
+                    segments.push(*segment);
+                    position = p;
+                    subpath_start = p;
+                },
+                Segment::LineTo(p) | Segment::CubicTo(_, _, p) => {
+                    segments.push(*segment);
+                    position = p;
+                },
+                Segment::ArcTo { radii, x_rotation, large_arc, sweep, end } => {
+                    let arc = CenterArc::from_endpoints(position, end, radii, x_rotation, large_arc, sweep);
+                    segments.extend(arc.to_cubics().into_iter().map(|[c1, c2, end]| Segment::CubicTo(c1, c2, end)));
+                    position = end;
+                },
+                Segment::Close => {
+                    segments.push(*segment);
+                    position = subpath_start;
+                },
+            }
+        }
+
+        return Self { segments };
+    }
+
+    /// Approximates every subpath with a closed polygon.
+    pub fn polygons(&self) -> Vec<Vec<Vec2<f32>>> {
+        let mut polygons = Vec::new();
+        let mut current: Vec<Vec2<f32>> = Vec::new();
+        let mut position = Vec2::new(0.0, 0.0);
+
+        for segment in &self.segments {
+            match *segment {
+                Segment::MoveTo(p) => {
+                    if current.len() > 1 {
+                        polygons.push(std::mem::take(&mut current));
+                    }
+                    current = vec![p];
+                    position = p;
+                },
+                Segment::LineTo(p) => {
+                    current.push(p);
+                    position = p;
+                },
+                Segment::ArcTo { radii, x_rotation, large_arc, sweep, end } => {
+                    let arc = CenterArc::from_endpoints(position, end, radii, x_rotation, large_arc, sweep);
+                    for i in 1..=CURVE_PIECES {
+                        let t = i as f32 / CURVE_PIECES as f32;
+                        current.push(arc.point(arc.start_angle + arc.sweep_angle * t));
+                    }
+                    position = end;
+                },
+                Segment::CubicTo(c1, c2, end) => {
+                    for i in 1..=CURVE_PIECES {
+                        let t = i as f32 / CURVE_PIECES as f32;
+                        let s = 1.0 - t;
+                        current.push(position * (s * s * s) + c1 * (3.0 * s * s * t) + c2 * (3.0 * s * t * t) + end * (t * t * t));
+                    }
+                    position = end;
+                },
+                Segment::Close => {
+                    if let Some(first) = current.first() {
+                        position = *first;
+                    }
+                    if current.len() > 1 {
+                        polygons.push(std::mem::take(&mut current));
+                    }
+                    current = vec![position];
+                },
+            }
+        }
+
+        if current.len() > 1 {
+            polygons.push(current);
+        }
+
+        return polygons;
+    }
+
+    /// Checks whether point lies inside of the outline using nonzero rule.
+    pub fn contains(&self, point: Vec2<f32>) -> bool {
+        return winding_number(&self.polygons(), point) != 0;
+    }
+}
+
+fn winding_number(polygons: &[Vec<Vec2<f32>>], point: Vec2<f32>) -> i32 {
+    let mut winding = 0;
+
+    for polygon in polygons {
+        for i in 0..polygon.len() {
+            let a = polygon[i];
+            let b = polygon[(i + 1) % polygon.len()];
+            let cross = (b.x() - a.x()) * (point.y() - a.y()) - (point.x() - a.x()) * (b.y() - a.y());
+
+            if a.y() <= point.y() {
+                if b.y() > point.y() && cross > 0.0 {
+                    winding += 1;
+                }
+            } else if b.y() <= point.y() && cross < 0.0 {
+                winding -= 1;
+            }
+        }
+    }
+
+    return winding;
+}
+
+/// Estimates fraction of the unit cell covered by union of the outlines
+/// by sampling `samples * samples` points on a regular grid.
+pub fn sampled_coverage(outlines: &[Outline], samples: usize) -> f32 {
+    if outlines.is_empty() || samples == 0 {
+        return 0.0;
+    }
+
+    let polygons: Vec<_> = outlines.iter().map(|outline| outline.polygons()).collect();
+    let mut covered = 0;
+
+    for x in 0..samples {
+        for y in 0..samples {
+            let point = Vec2::new((x as f32 + 0.5) / samples as f32, (y as f32 + 0.5) / samples as f32);
+
+            if polygons.iter().any(|polygons| winding_number(polygons, point) != 0) {
+                covered += 1;
+            }
+        }
+    }
+
+    return covered as f32 / (samples * samples) as f32;
+}
+
+impl Outline {
+    /// Converts svg path commands to outline, relative and shorthand commands are resolved.
+    pub fn from_path_commands(commands: &[Command]) -> Self {
+        let mut segments = Vec::with_capacity(commands.len());
+        let mut position = Vec2::new(0.0, 0.0);
+        let mut subpath_start = position;
+        // Control points of the previous curve, used by smooth curves
+        let mut last_cubic_control: Option<Vec2<f32>> = None;
+        let mut last_quadratic_control: Option<Vec2<f32>> = None;
+
+        for command in commands {
+            let (relative, parameters) = match command {
+                Command::Move(p, params) |
+                Command::Line(p, params) |
+                Command::HorizontalLine(p, params) |
+                Command::VerticalLine(p, params) |
+                Command::QuadraticCurve(p, params) |
+                Command::SmoothQuadraticCurve(p, params) |
+                Command::CubicCurve(p, params) |
+                Command::SmoothCubicCurve(p, params) |
+                Command::EllipticalArc(p, params) => (*p == Position::Relative, params.to_vec()),
+                Command::Close => (false, Vec::new()),
+            };
+            let absolute = |origin: Vec2<f32>, x: f32, y: f32| if relative { origin + Vec2::new(x, y) } else { Vec2::new(x, y) };
+            let reflected = |control: Option<Vec2<f32>>, position: Vec2<f32>| control.map_or(position, |c| position * 2.0 - c);
+
+            match command {
+                Command::Move(..) => {
+                    for (i, pair) in parameters.chunks_exact(2).enumerate() {
+                        position = absolute(position, pair[0], pair[1]);
+                        // Following pairs are implicit line commands
+                        segments.push(if i == 0 { Segment::MoveTo(position) } else { Segment::LineTo(position) });
+                        if i == 0 {
+                            subpath_start = position;
+                        }
+                    }
+                },
+                Command::Line(..) => {
+                    for pair in parameters.chunks_exact(2) {
+                        position = absolute(position, pair[0], pair[1]);
+                        segments.push(Segment::LineTo(position));
+                    }
+                },
+                Command::HorizontalLine(..) => {
+                    for x in &parameters {
+                        position = Vec2::new(if relative { position.x() + x } else { *x }, position.y());
+                        segments.push(Segment::LineTo(position));
+                    }
+                },
+                Command::VerticalLine(..) => {
+                    for y in &parameters {
+                        position = Vec2::new(position.x(), if relative { position.y() + y } else { *y });
+                        segments.push(Segment::LineTo(position));
+                    }
+                },
+                Command::CubicCurve(..) | Command::SmoothCubicCurve(..) => {
+                    let smooth = matches!(command, Command::SmoothCubicCurve(..));
+                    for params in parameters.chunks_exact(if smooth { 4 } else { 6 }) {
+                        let (c1, rest) = if smooth {
+                            (reflected(last_cubic_control, position), params)
+                        } else {
+                            (absolute(position, params[0], params[1]), &params[2..])
+                        };
+                        let c2 = absolute(position, rest[0], rest[1]);
+                        let end = absolute(position, rest[2], rest[3]);
+                        segments.push(Segment::CubicTo(c1, c2, end));
+                        last_cubic_control = Some(c2);
+                        position = end;
+                    }
+                    last_quadratic_control = None;
+                    continue;
+                },
+                Command::QuadraticCurve(..) | Command::SmoothQuadraticCurve(..) => {
+                    let smooth = matches!(command, Command::SmoothQuadraticCurve(..));
+                    for params in parameters.chunks_exact(if smooth { 2 } else { 4 }) {
+                        let (control, rest) = if smooth {
+                            (reflected(last_quadratic_control, position), params)
+                        } else {
+                            (absolute(position, params[0], params[1]), &params[2..])
+                        };
+                        let end = absolute(position, rest[0], rest[1]);
+                        // Quadratic curve is exactly representable as cubic one
+                        let c1 = position + (control - position) * (2.0 / 3.0);
+                        let c2 = end + (control - end) * (2.0 / 3.0);
+                        segments.push(Segment::CubicTo(c1, c2, end));
+                        last_quadratic_control = Some(control);
+                        position = end;
+                    }
+                    last_cubic_control = None;
+                    continue;
+                },
+                Command::EllipticalArc(..) => {
+                    for params in parameters.chunks_exact(7) {
+                        let end = absolute(position, params[5], params[6]);
+                        segments.push(Segment::ArcTo {
+                            radii: Vec2::new(params[0], params[1]),
+                            x_rotation: params[2],
+                            large_arc: params[3] != 0.0,
+                            sweep: params[4] != 0.0,
+                            end
+                        });
+                        position = end;
+                    }
+                },
+                Command::Close => {
+                    segments.push(Segment::Close);
+                    position = subpath_start;
+                },
+            }
+
+            last_cubic_control = None;
+            last_quadratic_control = None;
+        }
+
+        return Self { segments };
+    }
+}
+
+impl Outline {
+    /// Svg path data of the outline.
+    pub fn to_path_data(&self) -> Data {
+        let mut data = Data::new();
+
+        for segment in &self.segments {
+            data = match *segment {
+                Segment::MoveTo(p) => data.move_to((p.x(), p.y())),
+                Segment::LineTo(p) => data.line_to((p.x(), p.y())),
+                Segment::ArcTo { radii, x_rotation, large_arc, sweep, end } => data.elliptical_arc_to((
+                    radii.x(), radii.y(),
+                    x_rotation, large_arc as u8, sweep as u8,
+                    end.x(), end.y()
+                )),
+                Segment::CubicTo(c1, c2, end) => data.cubic_curve_to((c1.x(), c1.y(), c2.x(), c2.y(), end.x(), end.y())),
+                Segment::Close => data.close(),
+            };
+        }
+
+        return data;
+    }
+}
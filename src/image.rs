@@ -1,6 +1,258 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
 use crate::vec2::Vec2;
 
 pub trait Image {
     fn size(&self) -> Vec2<usize>;
     fn get_pixel_brightness(&self, pos: Vec2<usize>) -> f32;
+
+    /// RGB colour of the pixel with components from 0 to 1, grayscale images don't need to implement it.
+    fn get_pixel_colour(&self, pos: Vec2<usize>) -> [f32; 3] {
+        let brightness = self.get_pixel_brightness(pos);
+        return [brightness; 3];
+    }
+}
+
+/// Part of the source image sampled for every tile.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sampling {
+    /// All tiles of the generator block share the sample of the whole source image block.
+    #[default]
+    Block,
+    /// Every tile samples its own part of the source image block, so the same pattern reproduces finer detail.
+    Tile
+}
+
+impl Sampling {
+    /// Top left pixel and size of the source image region sampled for the tile at the given row and column.
+    pub(crate) fn region(&self, row: usize, col: usize, block_size: Vec2<usize>, source_block_size: Vec2<usize>) -> (Vec2<usize>, Vec2<usize>) {
+        let block_start = Vec2::new(col / block_size.y(), row / block_size.x()) * source_block_size;
+
+        return match self {
+            Sampling::Block => (block_start, source_block_size),
+            Sampling::Tile => {
+                // Block is split as evenly as possible when its size is not a multiple of the tiles count
+                let split = |index: usize, count: usize, length: usize| (index * length / count, (index + 1) * length / count);
+                let (x0, x1) = split(col % block_size.y(), block_size.y(), source_block_size.x());
+                let (y0, y1) = split(row % block_size.x(), block_size.x(), source_block_size.y());
+                (block_start + Vec2::new(x0, y0), Vec2::new((x1 - x0).max(1), (y1 - y0).max(1)))
+            },
+        };
+    }
+}
+
+/// Statistics of the source image block which drive tiles of a single generator.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BlockSample {
+    brightness: f32,
+    variance: f32,
+    contrast: f32,
+    colour: [f32; 3],
+    edge_direction: f32,
+    coherence: f32,
+    corners: [f32; 4]
+}
+
+impl BlockSample {
+    /// Uniform block of the given brightness.
+    pub const fn new(brightness: f32) -> Self {
+        return Self {
+            brightness,
+            variance: 0.0,
+            contrast: 0.0,
+            colour: [brightness; 3],
+            edge_direction: 0.0,
+            coherence: 0.0,
+            corners: [brightness; 4]
+        };
+    }
+
+    /// Samples image block of the given size, block must lie inside of the image.
+    pub fn sample<TImage: Image>(image: &TImage, start: Vec2<usize>, size: Vec2<usize>) -> Self {
+        let image_size = image.size();
+        let pixel = |x: usize, y: usize| image.get_pixel_brightness(Vec2::new(
+            x.min(image_size.x() - 1),
+            y.min(image_size.y() - 1)
+        ));
+
+        let mut brightness = 0.0;
+        let mut squares_sum = 0.0;
+        let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+        let mut colour = [0.0; 3];
+        // Structure tensor components
+        let (mut jxx, mut jyy, mut jxy) = (0.0, 0.0, 0.0);
+
+        for x in start.x()..start.x() + size.x() {
+            for y in start.y()..start.y() + size.y() {
+                let value = pixel(x, y);
+                brightness += value;
+                squares_sum += value * value;
+                min = min.min(value);
+                max = max.max(value);
+
+                let pixel_colour = image.get_pixel_colour(Vec2::new(x, y));
+                for (sum, component) in colour.iter_mut().zip(pixel_colour) {
+                    *sum += component;
+                }
+
+                let gx = (pixel(x + 1, y) - pixel(x.saturating_sub(1), y)) / 2.0;
+                let gy = (pixel(x, y + 1) - pixel(x, y.saturating_sub(1))) / 2.0;
+                jxx += gx * gx;
+                jyy += gy * gy;
+                jxy += gx * gy;
+            }
+        }
+
+        // Corners are shared by neighbouring blocks, so they are sampled symmetrically around the grid point
+        let corner = |x: usize, y: usize| (pixel(x.saturating_sub(1), y.saturating_sub(1)) + pixel(x, y.saturating_sub(1)) +
+            pixel(x.saturating_sub(1), y) + pixel(x, y)) / 4.0;
+        let end = start + size;
+        let corners = [corner(start.x(), start.y()), corner(end.x(), start.y()), corner(end.x(), end.y()), corner(start.x(), end.y())];
+
+        let pixels_count = (size.x() * size.y()).max(1) as f32;
+        let mean = brightness / pixels_count;
+        let gradient_direction = 0.5 * f32::atan2(2.0 * jxy, jxx - jyy);
+        let coherence = if jxx + jyy > f32::EPSILON {
+            ((jxx - jyy).powi(2) + 4.0 * jxy * jxy).sqrt() / (jxx + jyy)
+        } else {
+            0.0
+        };
+
+        return Self {
+            brightness: mean,
+            variance: (squares_sum / pixels_count - mean * mean).max(0.0),
+            contrast: if max >= min { max - min } else { 0.0 },
+            colour: colour.map(|sum| sum / pixels_count),
+            edge_direction: (gradient_direction + FRAC_PI_2).rem_euclid(PI),
+            coherence,
+            corners
+        };
+    }
+
+    pub fn with_brightness(mut self, brightness: f32) -> Self {
+        self.brightness = brightness;
+        return self;
+    }
+
+    pub fn with_variance(mut self, variance: f32) -> Self {
+        self.variance = variance;
+        return self;
+    }
+
+    pub fn with_contrast(mut self, contrast: f32) -> Self {
+        self.contrast = contrast;
+        return self;
+    }
+
+    pub fn with_colour(mut self, colour: [f32; 3]) -> Self {
+        self.colour = colour;
+        return self;
+    }
+
+    pub fn with_edge(mut self, edge_direction: f32, coherence: f32) -> Self {
+        self.edge_direction = edge_direction.rem_euclid(PI);
+        self.coherence = coherence;
+        return self;
+    }
+
+    pub fn with_corners(mut self, corners: [f32; 4]) -> Self {
+        self.corners = corners;
+        return self;
+    }
+
+    /// Mean brightness of the block.
+    #[inline]
+    pub fn brightness(&self) -> f32 {
+        return self.brightness;
+    }
+
+    /// Variance of pixels brightness.
+    #[inline]
+    pub fn variance(&self) -> f32 {
+        return self.variance;
+    }
+
+    /// Local contrast, difference between the brightest and the darkest pixel of the block.
+    #[inline]
+    pub fn contrast(&self) -> f32 {
+        return self.contrast;
+    }
+
+    /// Mean RGB colour of the block.
+    #[inline]
+    pub fn colour(&self) -> [f32; 3] {
+        return self.colour;
+    }
+
+    /// Dominant direction of edges in the block, in radians within `[0, PI)`,
+    /// measured clockwise from `x` axis as `y` axis points down.
+    #[inline]
+    pub fn edge_direction(&self) -> f32 {
+        return self.edge_direction;
+    }
+
+    /// How strongly edges in the block follow the dominant direction, from 0 (flat or noisy) to 1 (single straight edge).
+    #[inline]
+    pub fn coherence(&self) -> f32 {
+        return self.coherence;
+    }
+
+    /// Brightness in the top left, top right, bottom right and bottom left corners of the block,
+    /// corners shared by neighbouring blocks have equal values.
+    #[inline]
+    pub fn corners(&self) -> [f32; 4] {
+        return self.corners;
+    }
+}
+
+/// Source images shared by the tests of the writers and generators.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::vec2::Vec2;
+
+    use super::Image;
+
+    /// Image of the given size and brightness.
+    pub struct Uniform(pub Vec2<usize>, pub f32);
+
+    impl Image for Uniform {
+        fn size(&self) -> Vec2<usize> {
+            return self.0;
+        }
+
+        fn get_pixel_brightness(&self, _pos: Vec2<usize>) -> f32 {
+            return self.1;
+        }
+    }
+
+    /// Gradient from black on the left to white on the right, so tiles of every brightness are drawn.
+    pub struct Gradient(pub Vec2<usize>);
+
+    impl Image for Gradient {
+        fn size(&self) -> Vec2<usize> {
+            return self.0;
+        }
+
+        fn get_pixel_brightness(&self, pos: Vec2<usize>) -> f32 {
+            return pos.x() as f32 / (self.0.x() - 1) as f32;
+        }
+    }
+
+    /// Columns of pixels coloured from left to right with the given height, brightness is mid gray.
+    pub struct Stripes(pub Vec<[f32; 3]>, pub usize);
+
+    impl Image for Stripes {
+        fn size(&self) -> Vec2<usize> {
+            return Vec2::new(self.0.len(), self.1);
+        }
+
+        fn get_pixel_brightness(&self, _pos: Vec2<usize>) -> f32 {
+            return 0.5;
+        }
+
+        fn get_pixel_colour(&self, pos: Vec2<usize>) -> [f32; 3] {
+            return self.0[pos.x()];
+        }
+    }
 }
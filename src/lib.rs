@@ -4,7 +4,20 @@ pub mod image;
 pub mod generator;
 pub mod truchet_image;
 pub mod to_svg;
+pub mod to_postscript;
+pub mod to_text;
+pub mod dither;
+pub mod geometry;
+pub mod coverage;
+pub mod strand;
+pub mod dxf;
+pub mod eps;
+pub mod terminal;
+pub mod svg_writer;
+#[cfg(feature = "pdf")]
+pub mod pdf;
 
 mod utils;
+mod edge_solver;
 
 pub use svg;
@@ -0,0 +1,214 @@
+use std::io::{self, Write};
+
+use crate::{vec2::Vec2, geometry::{Outline, Segment}, generator::Generator, truchet_image::TruchetImage, utils::format_number as number};
+
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+/// Space around the trimmed page reserved for crop marks, in millimetres.
+const CROP_MARKS_AREA: f32 = 10.0;
+const CROP_MARK_OFFSET: f32 = 3.0;
+const CROP_MARK_LENGTH: f32 = 5.0;
+
+/// Page of the exported document, sizes are in millimetres.
+#[derive(Clone, Copy, Debug)]
+pub struct PdfPage {
+    width: f32,
+    height: f32,
+    margin: f32,
+    crop_marks: bool
+}
+
+impl PdfPage {
+    pub const fn new(width: f32, height: f32) -> Self {
+        return Self { width, height, margin: 0.0, crop_marks: false };
+    }
+
+    pub const fn a4() -> Self {
+        return Self::new(210.0, 297.0);
+    }
+
+    pub const fn a3() -> Self {
+        return Self::new(297.0, 420.0);
+    }
+
+    /// Blank space between the page border and the image.
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin.max(0.0);
+        return self;
+    }
+
+    /// Enlarges the page by area with crop marks at corners of the trimmed page.
+    pub fn with_crop_marks(mut self, crop_marks: bool) -> Self {
+        self.crop_marks = crop_marks;
+        return self;
+    }
+}
+
+/// Writes image as a single page PDF, tiles are filled vector paths with arcs converted to Bézier curves.
+///
+/// Page content is streamed tile by tile, so the document is never held in memory,
+/// wrapping `writer` into [`std::io::BufWriter`] is recommended.
+pub fn write_pdf<TGenerator: Generator, W: Write>(image: &TruchetImage<TGenerator>, page: &PdfPage, writer: W) -> io::Result<()> {
+    let mut pdf = PdfWriter { writer, written: 0, offsets: Vec::new() };
+    let bleed = if page.crop_marks { CROP_MARKS_AREA } else { 0.0 };
+    let media = Vec2::new(page.width + 2.0 * bleed, page.height + 2.0 * bleed) * POINTS_PER_MM;
+    let trim = [bleed, bleed, bleed + page.width, bleed + page.height].map(|mm| mm * POINTS_PER_MM);
+
+    pdf.write(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n")?;
+    pdf.object(1, "<< /Type /Catalog /Pages 2 0 R >>")?;
+    pdf.object(2, "<< /Type /Pages /Kids [3 0 R] /Count 1 >>")?;
+    pdf.object(3, &format!(
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /TrimBox [{} {} {} {}] /Contents 4 0 R /Resources << >> >>",
+        number(media.x()), number(media.y()), number(trim[0]), number(trim[1]), number(trim[2]), number(trim[3])
+    ))?;
+
+    // Length of the content is not known until it is streamed, so it is written as a separate object
+    pdf.start_object(4)?;
+    pdf.write(b"<< /Length 5 0 R >>\nstream\n")?;
+    let content_start = pdf.written;
+
+    if page.crop_marks {
+        write_crop_marks(&mut pdf, trim)?;
+    }
+    write_tiles(&mut pdf, image, page, bleed)?;
+
+    let content_length = pdf.written - content_start;
+    pdf.write(b"\nendstream\nendobj\n")?;
+    pdf.object(5, &content_length.to_string())?;
+
+    let xref_offset = pdf.written;
+    pdf.write(format!("xref\n0 {}\n0000000000 65535 f \n", pdf.offsets.len() + 1).as_bytes())?;
+    for offset in pdf.offsets.clone() {
+        pdf.write(format!("{:010} 00000 n \n", offset).as_bytes())?;
+    }
+    pdf.write(format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", pdf.offsets.len() + 1, xref_offset).as_bytes())?;
+
+    return pdf.writer.flush();
+}
+
+/// Keeps track of byte offsets of objects for the cross-reference table.
+struct PdfWriter<W: Write> {
+    writer: W,
+    written: usize,
+    offsets: Vec<usize>
+}
+
+impl<W: Write> PdfWriter<W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.writer.write_all(bytes)?;
+        self.written += bytes.len();
+        return Ok(());
+    }
+
+    /// Objects must be started in order of their numbers.
+    fn start_object(&mut self, id: usize) -> io::Result<()> {
+        self.offsets.push(self.written);
+        return self.write(format!("{} 0 obj\n", id).as_bytes());
+    }
+
+    fn object(&mut self, id: usize, content: &str) -> io::Result<()> {
+        self.start_object(id)?;
+        return self.write(format!("{}\nendobj\n", content).as_bytes());
+    }
+}
+
+fn write_crop_marks<W: Write>(pdf: &mut PdfWriter<W>, [left, bottom, right, top]: [f32; 4]) -> io::Result<()> {
+    let offset = CROP_MARK_OFFSET * POINTS_PER_MM;
+    let length = CROP_MARK_LENGTH * POINTS_PER_MM;
+    pdf.write(b"q 0.25 w 0 G\n")?;
+
+    for (x, y, outwards_x, outwards_y) in [(left, bottom, -1.0, -1.0), (right, bottom, 1.0, -1.0), (right, top, 1.0, 1.0), (left, top, -1.0, 1.0)] {
+        // Horizontal mark continues the vertical trim line and vice versa
+        let horizontal_start = x + outwards_x * offset;
+        let vertical_start = y + outwards_y * offset;
+        pdf.write(format!(
+            "{} {} m {} {} l {} {} m {} {} l\n",
+            number(horizontal_start), number(y), number(horizontal_start + outwards_x * length), number(y),
+            number(x), number(vertical_start), number(x), number(vertical_start + outwards_y * length)
+        ).as_bytes())?;
+    }
+
+    return pdf.write(b"S Q\n");
+}
+
+fn write_tiles<TGenerator: Generator, W: Write>(pdf: &mut PdfWriter<W>, image: &TruchetImage<TGenerator>, page: &PdfPage, bleed: f32) -> io::Result<()> {
+    let grid_size = image.grid_size();
+    let (rows, cols) = (grid_size.x().max(1) as f32, grid_size.y().max(1) as f32);
+    let area = Vec2::new((page.width - 2.0 * page.margin).max(0.0), (page.height - 2.0 * page.margin).max(0.0));
+    let tile_size = (area.x() / cols).min(area.y() / rows);
+
+    // Image is centred, tiles are drawn in cell units with y axis pointing down as in svg
+    let left = bleed + page.margin + (area.x() - tile_size * cols) / 2.0;
+    let top = bleed + page.height - page.margin - (area.y() - tile_size * rows) / 2.0;
+    pdf.write(format!(
+        "q {} 0 0 {} {} {} cm 0 g\n",
+        number(tile_size * POINTS_PER_MM), number(-tile_size * POINTS_PER_MM), number(left * POINTS_PER_MM), number(top * POINTS_PER_MM)
+    ).as_bytes())?;
+
+    for placed in image.tiles() {
+        for outline in placed.outlines() {
+            write_outline(pdf, &outline)?;
+        }
+    }
+
+    return pdf.write(b"Q\n");
+}
+
+fn write_outline<W: Write>(pdf: &mut PdfWriter<W>, outline: &Outline) -> io::Result<()> {
+    let mut path = String::new();
+
+    for segment in outline.with_cubic_arcs().segments() {
+        match *segment {
+            Segment::MoveTo(p) => path += &format!("{} {} m ", number(p.x()), number(p.y())),
+            Segment::LineTo(p) => path += &format!("{} {} l ", number(p.x()), number(p.y())),
+            Segment::CubicTo(c1, c2, end) => path += &format!(
+                "{} {} {} {} {} {} c ",
+                number(c1.x()), number(c1.y()), number(c2.x()), number(c2.y()), number(end.x()), number(end.y())
+            ),
+            Segment::Close => path += "h ",
+            Segment::ArcTo { .. } => unreachable!("arcs are converted to curves"),
+        }
+    }
+
+    // Every outline is filled separately, as outlines of the tile may overlap in opposite directions
+    path += "f\n";
+    return pdf.write(path.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generator, image::fixtures::Uniform, truchet_image::generate, vec2::Vec2};
+
+    use super::{write_pdf, PdfPage};
+
+    fn line_at(pdf: &[u8], offset: usize) -> &str {
+        let end = pdf[offset..].iter().position(|byte| *byte == b'\n').map_or(pdf.len(), |length| offset + length);
+        return std::str::from_utf8(&pdf[offset..end]).unwrap();
+    }
+
+    #[test]
+    fn xref_offsets_point_at_objects() {
+        let image = generate(&Uniform(Vec2::new(12, 12), 0.5), generator::circles(Vec2::new(3, 3)));
+        let mut pdf = Vec::new();
+        write_pdf(&image, &PdfPage::a4().with_margin(10.0).with_crop_marks(true), &mut pdf).unwrap();
+
+        // Header has binary bytes, so only the tail after the content is read as text
+        let tail_start = pdf.windows(9).rposition(|window| window == b"startxref").unwrap();
+        let xref_offset: usize = line_at(&pdf, tail_start + 10).parse().unwrap();
+        assert_eq!(line_at(&pdf, xref_offset), "xref");
+
+        let subsection = line_at(&pdf, xref_offset + 5);
+        let count: usize = subsection.strip_prefix("0 ").unwrap().parse().unwrap();
+        assert_eq!(count, 6);
+
+        let entries = xref_offset + 5 + subsection.len() + 1;
+        assert_eq!(line_at(&pdf, entries), "0000000000 65535 f ");
+        for id in 1..count {
+            // Every entry is exactly 20 bytes long
+            let entry = line_at(&pdf, entries + 20 * id);
+            assert!(entry.ends_with(" 00000 n "), "{}", entry);
+
+            let offset: usize = entry[..10].parse().unwrap();
+            assert_eq!(line_at(&pdf, offset), format!("{} 0 obj", id));
+        }
+    }
+}
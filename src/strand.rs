@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use rand::seq::SliceRandom;
+use svg::{node::element::{Group, Path}, Node};
+
+use crate::{vec2::Vec2, geometry::Outline, tile::{traits::Tile, edge::{Side, Port}}};
+
+/// Line made of strand pieces of neighbouring tiles, either closed loop or path ending at the image border.
+#[derive(Clone, Debug)]
+pub struct Strand {
+    points: Vec<Vec2<f32>>,
+    bands: Vec<Outline>,
+    closed: bool
+}
+
+impl Strand {
+    /// Centre line of the strand in tile units, `x` goes along columns and `y` along rows.
+    #[inline]
+    pub fn points(&self) -> &[Vec2<f32>] {
+        return &self.points;
+    }
+
+    /// Filled shapes of all tiles pieces the strand is made of, in tile units.
+    #[inline]
+    pub fn bands(&self) -> &[Outline] {
+        return &self.bands;
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        return self.closed;
+    }
+
+    /// Length of the centre line.
+    pub fn length(&self) -> f32 {
+        let mut length: f32 = self.points.windows(2)
+            .map(|pair| distance(pair[0], pair[1]))
+            .sum();
+
+        if self.closed && self.points.len() > 1 {
+            length += distance(self.points[self.points.len() - 1], self.points[0]);
+        }
+
+        return length;
+    }
+
+    /// Area enclosed by the loop, open strands enclose nothing.
+    pub fn area(&self) -> f32 {
+        if !self.closed {
+            return 0.0;
+        }
+
+        let mut doubled_area = 0.0;
+        for i in 0..self.points.len() {
+            let a = self.points[i];
+            let b = self.points[(i + 1) % self.points.len()];
+            doubled_area += a.x() * b.y() - b.x() * a.y();
+        }
+
+        return doubled_area.abs() / 2.0;
+    }
+}
+
+/// How colours of the palette are assigned to strands.
+#[derive(Clone, Debug)]
+pub enum StrandColouring {
+    /// Random colour of the palette for every strand.
+    Random(Vec<String>),
+    /// Palette is spread from the shortest to the longest strand.
+    ByLength(Vec<String>),
+    /// Palette is spread from the smallest to the largest enclosed area.
+    ByArea(Vec<String>)
+}
+
+impl StrandColouring {
+    fn colours(&self, strands: &[Strand]) -> Vec<String> {
+        let (palette, measure): (&Vec<String>, fn(&Strand) -> f32) = match self {
+            StrandColouring::Random(palette) => {
+                let mut rng = rand::thread_rng();
+                return strands.iter()
+                    .map(|_| palette.choose(&mut rng).cloned().unwrap_or_else(|| "black".to_owned()))
+                    .collect();
+            },
+            StrandColouring::ByLength(palette) => (palette, Strand::length),
+            StrandColouring::ByArea(palette) => (palette, Strand::area),
+        };
+
+        if palette.is_empty() {
+            return vec!["black".to_owned(); strands.len()];
+        }
+
+        let values: Vec<f32> = strands.iter().map(measure).collect();
+        let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+        return values.iter()
+            .map(|value| {
+                let t = if max - min > f32::EPSILON { (value - min) / (max - min) } else { 0.0 };
+                return palette[(t * (palette.len() - 1) as f32).round() as usize].clone();
+            })
+            .collect();
+    }
+}
+
+/// Renders bands of every strand filled with its own colour, in tile units like truchet image svg.
+pub fn strands_to_svg(strands: &[Strand], colouring: &StrandColouring) -> Box<dyn Node> {
+    let mut g = Group::new();
+
+    for (strand, colour) in strands.iter().zip(colouring.colours(strands)) {
+        let mut strand_group = Group::new().set("fill", colour);
+
+        for band in strand.bands() {
+            strand_group.append(Path::new().set("d", band.to_path_data()));
+        }
+
+        g.append(strand_group);
+    }
+
+    return Box::new(g);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum PortKey {
+    // Connection on the left side of the tile
+    Vertical(usize, usize, usize),
+    // Connection on the top side of the tile
+    Horizontal(usize, usize, usize),
+    // Connection which does not match the neighbour
+    Open(usize, usize, Side, usize)
+}
+
+struct Piece {
+    ends: [PortKey; 2],
+    centre_line: Vec<Vec2<f32>>,
+    bands: Vec<Outline>
+}
+
+/// Joins strand pieces of the grid of tiles, `grid_size` is number of rows and columns.
+pub fn trace_strands<'a, TTile: Tile + 'a>(grid_size: Vec2<usize>, tile_at: impl Fn(usize, usize) -> &'a TTile) -> Vec<Strand> {
+    let (rows, cols) = (grid_size.x(), grid_size.y());
+    let mut pieces = Vec::new();
+    let mut ports: HashMap<PortKey, Vec<(usize, usize)>> = HashMap::new();
+
+    for row in 0..rows {
+        for col in 0..cols {
+            let tile = tile_at(row, col);
+            let edges = tile.edges();
+            let neighbour_matches = |side: Side| {
+                let neighbour = match side {
+                    Side::Top if row > 0 => tile_at(row - 1, col),
+                    Side::Right if col + 1 < cols => tile_at(row, col + 1),
+                    Side::Bottom if row + 1 < rows => tile_at(row + 1, col),
+                    Side::Left if col > 0 => tile_at(row, col - 1),
+                    _ => return true,
+                };
+                return edges.matches(side, &neighbour.edges());
+            };
+            let key = |port: Port| {
+                if !neighbour_matches(port.side) {
+                    return PortKey::Open(row, col, port.side, port.connection);
+                }
+
+                return match port.side {
+                    Side::Top => PortKey::Horizontal(row, col, port.connection),
+                    Side::Bottom => PortKey::Horizontal(row + 1, col, port.connection),
+                    Side::Left => PortKey::Vertical(row, col, port.connection),
+                    Side::Right => PortKey::Vertical(row, col + 1, port.connection),
+                };
+            };
+
+            for link in tile.links() {
+                let offset = Vec2::new(col as f32, row as f32);
+                let ends = [key(link.from), key(link.to)];
+                let centre_line = link.centre_line.transformed(1.0, offset).polygons().into_iter().flatten().collect();
+                let bands = link.bands.iter().map(|band| band.transformed(1.0, offset)).collect();
+
+                for (end, port) in ends.iter().enumerate() {
+                    ports.entry(*port).or_default().push((pieces.len(), end));
+                }
+                pieces.push(Piece { ends, centre_line, bands });
+            }
+        }
+    }
+
+    let mut visited = vec![false; pieces.len()];
+    let mut strands = Vec::new();
+
+    for start in 0..pieces.len() {
+        if visited[start] {
+            continue;
+        }
+
+        visited[start] = true;
+        // Pieces with flag telling whether piece is walked from its second end to the first one
+        let mut forward = vec![(start, false)];
+        let closed = walk(&pieces, &ports, &mut visited, (start, 1), start, &mut forward);
+        let mut backward = Vec::new();
+        if !closed {
+            walk(&pieces, &ports, &mut visited, (start, 0), start, &mut backward);
+        }
+
+        let ordered = backward.into_iter()
+            .rev()
+            .map(|(piece, reversed)| (piece, !reversed))
+            .chain(forward);
+
+        let mut points: Vec<Vec2<f32>> = Vec::new();
+        let mut bands = Vec::new();
+        for (piece, reversed) in ordered {
+            let piece = &pieces[piece];
+            let mut line = piece.centre_line.clone();
+            if reversed {
+                line.reverse();
+            }
+
+            let skip = if points.last().is_some_and(|last| line.first().is_some_and(|first| distance(*last, *first) < 1e-4)) { 1 } else { 0 };
+            points.extend(line.into_iter().skip(skip));
+            bands.extend(piece.bands.iter().cloned());
+        }
+
+        if closed && points.len() > 1 && distance(points[0], points[points.len() - 1]) < 1e-4 {
+            points.pop();
+        }
+
+        strands.push(Strand { points, bands, closed });
+    }
+
+    return strands;
+}
+
+/// Follows pieces from the given piece end until the strand ends or returns to the `start` piece.
+fn walk(
+    pieces: &[Piece],
+    ports: &HashMap<PortKey, Vec<(usize, usize)>>,
+    visited: &mut [bool],
+    (mut piece, mut end): (usize, usize),
+    start: usize,
+    path: &mut Vec<(usize, bool)>
+) -> bool {
+    loop {
+        let port = pieces[piece].ends[end];
+        let next = ports[&port].iter().find(|(other, other_end)| (*other, *other_end) != (piece, end));
+
+        let Some(&(next_piece, next_end)) = next else {
+            return false;
+        };
+
+        if next_piece == start {
+            return true;
+        }
+
+        if visited[next_piece] {
+            return false;
+        }
+
+        visited[next_piece] = true;
+        // Entering through the second end means walking the piece backwards
+        path.push((next_piece, next_end == 1));
+        piece = next_piece;
+        end = 1 - next_end;
+    }
+}
+
+#[inline]
+fn distance(a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+    let d = a - b;
+    return (d.x() * d.x() + d.y() * d.y()).sqrt();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use crate::{tile::circle::ElasticCircleTile, vec2::Vec2};
+
+    use super::trace_strands;
+
+    #[test]
+    fn arcs_around_shared_corner_form_loop() {
+        // Every tile has one of its arcs around the centre of the grid
+        let tiles = [
+            [ElasticCircleTile::new(false, 0.1, 0.5), ElasticCircleTile::new(true, 0.1, 0.5)],
+            [ElasticCircleTile::new(true, 0.1, 0.5), ElasticCircleTile::new(false, 0.1, 0.5)]
+        ];
+        let strands = trace_strands(Vec2::new(2, 2), |row, col| &tiles[row][col]);
+
+        let loops: Vec<_> = strands.iter().filter(|strand| strand.is_closed()).collect();
+        assert_eq!(strands.len(), 5);
+        assert_eq!(loops.len(), 1);
+
+        let circle = loops[0];
+        assert_eq!(circle.bands().len(), 4);
+        assert!((circle.length() - PI).abs() < 0.05);
+        assert!((circle.area() - PI / 4.0).abs() < 0.05);
+        assert!(circle.points().iter().all(|p| ((p.x() - 1.0).hypot(p.y() - 1.0) - 0.5).abs() < 1e-3));
+    }
+
+    #[test]
+    fn strands_end_at_image_border() {
+        let tiles = [ElasticCircleTile::new(false, 0.1, 0.5)];
+        let strands = trace_strands(Vec2::new(1, 1), |_, col| &tiles[col]);
+
+        assert_eq!(strands.len(), 2);
+        assert!(strands.iter().all(|strand| !strand.is_closed() && strand.area() == 0.0));
+    }
+}
@@ -0,0 +1,68 @@
+use std::io::{self, Write};
+
+use svg::{node::{Text, element::Group}, Node};
+
+use crate::{generator::Generator, truchet_image::TruchetImage, to_svg::ToSVG};
+
+/// Placeholder for the image inside of the formatted document, splits it into the parts before and after the image.
+const IMAGE_PLACEHOLDER: &str = "__truchet_image__";
+
+/// Writes `document` with the image appended as its last child, tile by tile.
+///
+/// Output is the same as of `document.add(image.to_svg_node())`, but only a single tile node is held in memory at a time,
+/// so poster-size images can be written straight to a file. Wrapping `writer` into [`std::io::BufWriter`] is recommended.
+pub fn write_svg<TGenerator: Generator, TNode: Node + Clone, W: Write>(image: &TruchetImage<TGenerator>, document: &TNode, mut writer: W) -> io::Result<()>
+where
+    TGenerator::TileType: ToSVG
+{
+    let mut template = Clone::clone(document);
+    template.append(Text::new(IMAGE_PLACEHOLDER));
+    let template = template.to_string();
+    let (before, after) = template.rsplit_once(IMAGE_PLACEHOLDER).expect("Placeholder is in the document");
+
+    writer.write_all(before.as_bytes())?;
+
+    // Empty group is formatted as a self-closing element
+    if image.tiles().next().is_none() {
+        write!(writer, "{}", Group::new())?;
+    } else {
+        writer.write_all(b"<g>")?;
+        for placed in image.tiles() {
+            write!(writer, "\n{}", placed.to_svg_node())?;
+        }
+        writer.write_all(b"\n</g>")?;
+    }
+
+    writer.write_all(after.as_bytes())?;
+    return writer.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use svg::{node::element::Rectangle, Document};
+
+    use crate::{generator, image::{Image, fixtures::{Gradient, Uniform}}, to_svg::ToSVG, truchet_image::generate, vec2::Vec2};
+
+    use super::write_svg;
+
+    fn assert_same_as_tree<TImage: Image>(source: &TImage, document: Document) {
+        let image = generate(source, generator::circles(Vec2::new(1, 1)));
+        let mut streamed = Vec::new();
+        write_svg(&image, &document, &mut streamed).unwrap();
+
+        assert_eq!(String::from_utf8(streamed).unwrap(), document.add(image.to_svg_node()).to_string());
+    }
+
+    #[test]
+    fn streamed_image_is_the_same_as_tree() {
+        let document = Document::new().set("viewBox", (0, 0, 13, 7)).add(Rectangle::new().set("width", 13).set("height", 7));
+
+        assert_same_as_tree(&Gradient(Vec2::new(13, 7)), document);
+        assert_same_as_tree(&Gradient(Vec2::new(13, 7)), Document::new());
+    }
+
+    #[test]
+    fn streamed_empty_image_is_the_same_as_tree() {
+        assert_same_as_tree(&Uniform(Vec2::new(0, 0), 0.5), Document::new().set("viewBox", (0, 0, 1, 1)));
+    }
+}
@@ -0,0 +1,130 @@
+use std::io::{self, Write};
+
+use crate::{vec2::Vec2, image::Image, generator::Generator, truchet_image::TruchetImage, to_text::ToText};
+
+/// Writes preview of the image as lines of characters, every tile takes two columns.
+pub fn write_text<TGenerator: Generator, W: Write>(image: &TruchetImage<TGenerator>, writer: W) -> io::Result<()>
+where
+    TGenerator::TileType: ToText
+{
+    return write_preview(image, |_, _| None, writer);
+}
+
+/// Writes preview of the image coloured by the source image with ANSI truecolor escape codes.
+pub fn write_ansi<TGenerator: Generator, TImage: Image, W: Write>(image: &TruchetImage<TGenerator>, source: &TImage, writer: W) -> io::Result<()>
+where
+    TGenerator::TileType: ToText
+{
+    let mut cached: Option<(Vec2<usize>, [u8; 3])> = None;
+    let colour = |row: usize, col: usize| {
+        let (start, size) = image.source_block(row, col);
+
+        // Tiles sampled from the same region share the colour
+        if let Some((cached_start, colour)) = cached {
+            if cached_start == start {
+                return Some(colour);
+            }
+        }
+
+        let colour = mean_colour(source, start, size);
+        cached = Some((start, colour));
+        return Some(colour);
+    };
+
+    return write_preview(image, colour, writer);
+}
+
+fn write_preview<TGenerator: Generator, W: Write>(
+    image: &TruchetImage<TGenerator>,
+    mut colour: impl FnMut(usize, usize) -> Option<[u8; 3]>,
+    mut writer: W
+) -> io::Result<()>
+where
+    TGenerator::TileType: ToText
+{
+    let grid_size = image.grid_size();
+
+    for row in 0..grid_size.x() {
+        let mut line = String::new();
+        let mut current_colour = None;
+
+        for col in 0..grid_size.y() {
+            let tile_colour = colour(row, col);
+            if let Some([r, g, b]) = tile_colour.filter(|_| tile_colour != current_colour) {
+                line += &format!("\x1b[38;2;{};{};{}m", r, g, b);
+                current_colour = tile_colour;
+            }
+            line.extend(image.tile_at(row, col).to_text());
+        }
+
+        if current_colour.is_some() {
+            line += "\x1b[0m";
+        }
+        writeln!(writer, "{}", line)?;
+    }
+
+    return writer.flush();
+}
+
+fn mean_colour<TImage: Image>(image: &TImage, start: Vec2<usize>, size: Vec2<usize>) -> [u8; 3] {
+    let image_size = image.size();
+    let mut sum = [0.0; 3];
+    let mut count = 0;
+
+    for x in start.x()..(start.x() + size.x()).min(image_size.x()) {
+        for y in start.y()..(start.y() + size.y()).min(image_size.y()) {
+            for (sum, component) in sum.iter_mut().zip(image.get_pixel_colour(Vec2::new(x, y))) {
+                *sum += component;
+            }
+            count += 1;
+        }
+    }
+
+    return sum.map(|sum| (sum / count.max(1) as f32 * 255.0).round().clamp(0.0, 255.0) as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generator::PatternGenerator, image::fixtures::Stripes, tile::{circle::ElasticCircleTile, triangle::{ElasticTriangleTile, ElasticTileType}}, truchet_image::generate, vec2::Vec2};
+
+    use super::{write_ansi, write_text};
+
+    fn text(output: Vec<u8>) -> String {
+        return String::from_utf8(output).unwrap();
+    }
+
+    #[test]
+    fn triangles_are_drawn_with_two_glyphs() {
+        let triangle = |tile_type| ElasticTriangleTile::new(0.5, tile_type);
+        let pattern = PatternGenerator::from_slice([
+            [triangle(ElasticTileType::A), triangle(ElasticTileType::B)],
+            [triangle(ElasticTileType::C), triangle(ElasticTileType::D)]
+        ], Vec2::new(1, 1));
+        let mut output = Vec::new();
+        write_text(&generate(&Stripes(vec![[0.0; 3]; 2], 1), pattern), &mut output).unwrap();
+
+        assert_eq!(text(output), "◣◣◤◤◣◣◤◤\n◥◥◢◢◥◥◢◢\n");
+    }
+
+    #[test]
+    fn circles_are_drawn_with_arc_glyphs() {
+        let pattern = PatternGenerator::from_slice([[ElasticCircleTile::default(), ElasticCircleTile::default().flipped()]], Vec2::new(1, 1));
+        let mut output = Vec::new();
+        write_text(&generate(&Stripes(vec![[0.0; 3]], 1), pattern), &mut output).unwrap();
+
+        assert_eq!(text(output), "╯╭╮╰\n");
+    }
+
+    #[test]
+    fn colour_is_set_once_per_run() {
+        let (red, blue) = ([1.0, 0.0, 0.0], [0.0, 0.0, 1.0]);
+        let source = Stripes(vec![red, red, blue], 2);
+        let pattern = PatternGenerator::from_slice([[ElasticCircleTile::default()]], Vec2::new(1, 1));
+        let mut output = Vec::new();
+        write_ansi(&generate(&source, pattern), &source, &mut output).unwrap();
+
+        // Both red blocks share one escape and every line resets the colour
+        let line = "\x1b[38;2;255;0;0m╯╭╯╭\x1b[38;2;0;0;255m╯╭\x1b[0m\n";
+        assert_eq!(text(output), line.repeat(2));
+    }
+}
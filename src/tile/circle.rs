@@ -1,10 +1,13 @@
+use std::f32::consts::{FRAC_PI_4, PI, SQRT_2};
+
 use svg::{node::element::{Group, Path, path::Data}, Node};
 
-use crate::{to_svg::ToSVG, vec2::Vec2};
+use crate::{to_svg::ToSVG, to_postscript::ToPostScript, to_text::ToText, vec2::Vec2, geometry::Outline, utils::format_number};
 
-use super::traits::Tile;
+use super::{traits::Tile, symmetry::{Orientation, Symmetry}, edge::{EdgeSignature, Edge, EdgeColour, Connection, Link, Port, Side}, region::Region};
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ElasticCircleTile {
     flipped: bool,
     half_width: f32,
@@ -22,17 +25,39 @@ impl ElasticCircleTile {
         return self;
     }
 
+    /// Sets width of the bands.
+    #[inline]
+    pub fn set_width(&mut self, width: f32) {
+        self.half_width = width.clamp(0.0, 1.0) / 2.0;
+    }
+
+    /// Sets radius of the bands centre lines, arcs are centred in the cell corners.
+    #[inline]
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn width(&self) -> f32 {
+        return 2.0 * self.half_width;
+    }
+
+    #[inline]
+    pub fn radius(&self) -> f32 {
+        return self.radius;
+    }
+
     fn upper_arc(self) -> (Vec2<f32>, Vec2<f32>) {
         return ( 
-            Vec2::new(0.0, 0.5),
-            Vec2::new(0.5, 0.0)
+            Vec2::new(0.0, self.radius),
+            Vec2::new(self.radius, 0.0)
         );
     }
 
     fn lower_arc(self) -> (Vec2<f32>, Vec2<f32>) {
         return (
-            Vec2::new(1.0, 0.5),
-            Vec2::new(0.5, 1.0)
+            Vec2::new(1.0, 1.0 - self.radius),
+            Vec2::new(1.0 - self.radius, 1.0)
         );
     }
 }
@@ -49,6 +74,146 @@ impl Tile for ElasticCircleTile {
     fn set_brightness(&mut self, brightness: f32) {
         self.half_width = (1.0 - brightness) / 4.0;
     }
+
+    #[inline]
+    fn rotate(&mut self) {
+        self.flipped = !self.flipped;
+    }
+
+    #[inline]
+    fn mirror(&mut self) {
+        self.flipped = !self.flipped;
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        return Symmetry::from_orientations(&[
+            Orientation::new(2, false),
+            Orientation::new(1, true),
+            Orientation::new(3, true)
+        ]);
+    }
+
+    fn edges(&self) -> EdgeSignature {
+        // Upper arc crosses top and left sides, lower arc crosses bottom and right sides
+        let edge = |position: f32| Edge::new(EdgeColour::BACKGROUND, vec![
+            Connection::new(position, 2.0 * self.half_width, EdgeColour::FOREGROUND)
+        ]);
+        let edges = EdgeSignature::new(edge(self.radius), edge(1.0 - self.radius), edge(1.0 - self.radius), edge(self.radius));
+
+        return edges.oriented(Orientation::new(self.flipped as u8, false));
+    }
+
+    fn outlines(&self) -> Vec<Outline> {
+        let (upper_start, upper_end) = self.upper_arc();
+        let (lower_start, lower_end) = self.lower_arc();
+
+        let r_plus_hw = self.radius + self.half_width;
+        let r_minus_hw = self.radius - self.half_width;
+        let orientation = Orientation::new(self.flipped as u8, false);
+
+        let upper = Outline::new()
+            .move_to(Vec2::new(upper_start.x(), upper_start.y() + self.half_width))
+            .arc_to(r_plus_hw, false, false, Vec2::new(upper_end.x() + self.half_width, upper_end.y()))
+            .line_to(Vec2::new(upper_end.x() - self.half_width, upper_end.y()))
+            .arc_to(r_minus_hw, false, true, Vec2::new(upper_start.x(), upper_start.y() - self.half_width))
+            .close();
+
+        let lower = Outline::new()
+            .move_to(Vec2::new(lower_start.x(), lower_start.y() - self.half_width))
+            .arc_to(r_plus_hw, false, false, Vec2::new(lower_end.x() - self.half_width, lower_end.y()))
+            .line_to(Vec2::new(lower_end.x() + self.half_width, lower_end.y()))
+            .arc_to(r_minus_hw, false, true, Vec2::new(lower_start.x(), lower_start.y() + self.half_width))
+            .close();
+
+        return vec![upper.oriented(orientation), lower.oriented(orientation)];
+    }
+
+    fn links(&self) -> Vec<Link> {
+        let (upper_start, upper_end) = self.upper_arc();
+        let (lower_start, lower_end) = self.lower_arc();
+        let orientation = Orientation::new(self.flipped as u8, false);
+        let [upper_band, lower_band]: [Outline; 2] = self.outlines().try_into().unwrap();
+
+        // Ports of the upper and lower arcs turned together with the tile
+        let (upper, lower) = if self.flipped {
+            ((Side::Top, Side::Right), (Side::Bottom, Side::Left))
+        } else {
+            ((Side::Left, Side::Top), (Side::Right, Side::Bottom))
+        };
+
+        return vec![
+            Link {
+                from: Port::new(upper.0, 0),
+                to: Port::new(upper.1, 0),
+                centre_line: Outline::new().move_to(upper_start).arc_to(self.radius, false, false, upper_end).oriented(orientation),
+                bands: vec![upper_band]
+            },
+            Link {
+                from: Port::new(lower.0, 0),
+                to: Port::new(lower.1, 0),
+                centre_line: Outline::new().move_to(lower_start).arc_to(self.radius, false, false, lower_end).oriented(orientation),
+                bands: vec![lower_band]
+            }
+        ];
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        let (upper_start, upper_end) = self.upper_arc();
+        let (lower_start, lower_end) = self.lower_arc();
+        let orientation = Orientation::new(self.flipped as u8, false);
+
+        // Regions are bounded by the centre lines, bands are drawn over them
+        let upper = Outline::new()
+            .move_to(Vec2::new(0.0, 0.0))
+            .line_to(upper_start)
+            .arc_to(self.radius, false, false, upper_end)
+            .close();
+
+        let lower = Outline::new()
+            .move_to(Vec2::new(1.0, 1.0))
+            .line_to(lower_start)
+            .arc_to(self.radius, false, false, lower_end)
+            .close();
+
+        let middle = Outline::new()
+            .move_to(upper_end)
+            .line_to(Vec2::new(1.0, 0.0))
+            .line_to(lower_start)
+            .arc_to(self.radius, false, false, lower_end)
+            .line_to(Vec2::new(0.0, 1.0))
+            .line_to(upper_start)
+            .arc_to(self.radius, false, false, upper_end)
+            .close();
+
+        return vec![
+            Region::new(Vec2::new(0, 0), upper).oriented(orientation),
+            Region::new(Vec2::new(1, 1), lower).oriented(orientation),
+            Region::new(Vec2::new(1, 0), middle).oriented(orientation)
+        ];
+    }
+
+    /// Stroke is not taken into account
+    fn coverage(&self) -> f32 {
+        let outer = self.radius + self.half_width;
+        let inner = (self.radius - self.half_width).max(0.0);
+        let bands = 2.0 * FRAC_PI_4 * (outer * outer - inner * inner);
+
+        // Bands overlap near the tile centre when they are wide enough
+        let centres_distance = SQRT_2;
+        let overlap = if 2.0 * outer > centres_distance {
+            2.0 * outer * outer * (centres_distance / (2.0 * outer)).acos() -
+                centres_distance / 2.0 * (4.0 * outer * outer - centres_distance * centres_distance).sqrt()
+        } else {
+            0.0
+        };
+
+        return (bands - overlap).clamp(0.0, 1.0);
+    }
+
+    fn direction(&self) -> Option<f32> {
+        // Arcs are stretched along the diagonal which does not pass through their centres
+        return if self.flipped { Some(FRAC_PI_4) } else { Some(PI - FRAC_PI_4) };
+    }
 }
 
 impl ToSVG for ElasticCircleTile {
@@ -108,3 +273,28 @@ impl ToSVG for ElasticCircleTile {
         return Box::new(g);
     }
 }
+
+impl ToPostScript for ElasticCircleTile {
+    /// Bands around the top left and bottom right corners, takes half width, radius, flipped flag and cell origin.
+    fn postscript_procedures(&self) -> &'static str {
+        return "/arcs { gsave translate { 0.5 0.5 translate 90 rotate -0.5 -0.5 translate } if\n  \
+            2 dict begin /r exch def /hw exch def\n  \
+            0 0 r hw add 0 90 arc 0 0 r hw sub dup 0 lt { pop 0 } if 90 0 arcn h f\n  \
+            1 1 r hw add 180 270 arc 1 1 r hw sub dup 0 lt { pop 0 } if 270 180 arcn h f\n  \
+            end grestore } bind def";
+    }
+
+    fn to_postscript(&self, origin: Vec2<f32>) -> String {
+        return format!(
+            "{} {} {} {} {} arcs\n",
+            format_number(self.half_width), format_number(self.radius), self.flipped, format_number(origin.x()), format_number(origin.y())
+        );
+    }
+}
+
+impl ToText for ElasticCircleTile {
+    /// Arc around the top left corner is in the left column, unless the tile is flipped.
+    fn to_text(&self) -> [char; 2] {
+        return if self.flipped { ['╮', '╰'] } else { ['╯', '╭'] };
+    }
+}
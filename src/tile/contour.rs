@@ -0,0 +1,305 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use svg::{node::element::{Group, Path}, Node};
+
+use crate::{to_svg::ToSVG, to_postscript::ToPostScript, to_text::ToText, vec2::Vec2, geometry::Outline, image::BlockSample};
+
+use super::{traits::Tile, symmetry::{Orientation, Symmetry}, edge::{EdgeSignature, Edge, EdgeColour, Connection, Link, Port, Side}};
+
+/// Sides joined by contour segments for every marching squares case.
+///
+/// Case index has a bit set for every corner above the threshold: top left is 8, top right 4,
+/// bottom right 2 and bottom left 1. Saddle cases 5 and 10 are listed for the centre below the threshold.
+const CASES: [&[(Side, Side)]; 16] = [
+    &[],
+    &[(Side::Left, Side::Bottom)],
+    &[(Side::Bottom, Side::Right)],
+    &[(Side::Left, Side::Right)],
+    &[(Side::Top, Side::Right)],
+    &[(Side::Top, Side::Right), (Side::Left, Side::Bottom)],
+    &[(Side::Top, Side::Bottom)],
+    &[(Side::Left, Side::Top)],
+    &[(Side::Left, Side::Top)],
+    &[(Side::Top, Side::Bottom)],
+    &[(Side::Left, Side::Top), (Side::Bottom, Side::Right)],
+    &[(Side::Top, Side::Right)],
+    &[(Side::Left, Side::Right)],
+    &[(Side::Bottom, Side::Right)],
+    &[(Side::Left, Side::Bottom)],
+    &[]
+];
+
+/// Contour map tile, draws isolines of brightness interpolated between the cell corners.
+///
+/// Every threshold picks one of 16 marching squares cases, crossings are interpolated linearly along the sides,
+/// so isolines of neighbouring tiles sharing corner values join up.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContourTile {
+    /// Brightness in the top left, top right, bottom right and bottom left corners.
+    corners: [f32; 4],
+    thresholds: Vec<f32>,
+    half_width: f32
+}
+
+impl ContourTile {
+    pub fn new(thresholds: &[f32], width: f32) -> Self {
+        return Self { corners: [0.0; 4], thresholds: thresholds.to_vec(), half_width: width.clamp(0.0, 1.0) / 2.0 };
+    }
+
+    /// Sets brightness in the top left, top right, bottom right and bottom left corners.
+    #[inline]
+    pub fn set_corners(&mut self, corners: [f32; 4]) {
+        self.corners = corners;
+    }
+
+    #[inline]
+    pub fn corners(&self) -> [f32; 4] {
+        return self.corners;
+    }
+
+    #[inline]
+    pub fn thresholds(&self) -> &[f32] {
+        return &self.thresholds;
+    }
+
+    /// Marching squares case of the given threshold.
+    pub fn case(&self, threshold: f32) -> usize {
+        return self.corners.iter().fold(0, |case, &corner| case << 1 | (corner >= threshold) as usize);
+    }
+
+    /// Sides joined by isolines of the threshold, saddles are resolved by the mean of the corners.
+    fn sides(&self, threshold: f32) -> &'static [(Side, Side)] {
+        let case = self.case(threshold);
+        let centre_above = self.corners.iter().sum::<f32>() / 4.0 >= threshold;
+
+        return match case {
+            5 if centre_above => CASES[10],
+            10 if centre_above => CASES[5],
+            _ => CASES[case]
+        };
+    }
+
+    /// Position of the threshold crossing along the side.
+    fn crossing(&self, side: Side, threshold: f32) -> Option<f32> {
+        let [top_left, top_right, bottom_right, bottom_left] = self.corners;
+        let (from, to) = match side {
+            Side::Top => (top_left, top_right),
+            Side::Right => (top_right, bottom_right),
+            Side::Bottom => (bottom_left, bottom_right),
+            Side::Left => (top_left, bottom_left),
+        };
+
+        if (from >= threshold) == (to >= threshold) {
+            return None;
+        }
+
+        return Some(((threshold - from) / (to - from)).clamp(0.0, 1.0));
+    }
+
+    /// Crossings of all thresholds on the side, sorted by position.
+    fn crossings(&self, side: Side) -> Vec<f32> {
+        let mut crossings: Vec<f32> = self.thresholds.iter()
+            .filter_map(|&threshold| self.crossing(side, threshold))
+            .collect();
+        crossings.sort_by(f32::total_cmp);
+
+        return crossings;
+    }
+
+    /// Contour segments as pairs of ports with their points.
+    fn segments(&self) -> Vec<[(Port, Vec2<f32>); 2]> {
+        let crossings = Side::ALL.map(|side| self.crossings(side));
+        let end = |side: Side, threshold: f32| -> (Port, Vec2<f32>) {
+            let position = self.crossing(side, threshold).unwrap_or(0.5);
+            let sorted = &crossings[side as usize];
+            let connection = sorted.iter().position(|&p| p == position).unwrap_or(0);
+
+            return (Port::new(side, connection), point(side, position));
+        };
+
+        return self.thresholds.iter()
+            .flat_map(|&threshold| self.sides(threshold).iter().map(move |&(from, to)| [end(from, threshold), end(to, threshold)]))
+            .collect();
+    }
+
+    /// Band around the segment, its ends are cut along the sides so bands of neighbouring tiles join exactly.
+    fn band(&self, [(from_port, from), (to_port, to)]: [(Port, Vec2<f32>); 2]) -> Outline {
+        let direction = to - from;
+        let ends = |port: Port, p: Vec2<f32>| {
+            let along = match port.side {
+                Side::Top | Side::Bottom => Vec2::new(self.half_width, 0.0),
+                Side::Left | Side::Right => Vec2::new(0.0, self.half_width),
+            };
+            let (a, b) = (clamp(p + along), clamp(p - along));
+            let a_on_left = direction.x() * (a.y() - p.y()) - direction.y() * (a.x() - p.x()) < 0.0;
+
+            return if a_on_left { (a, b) } else { (b, a) };
+        };
+
+        let (from_left, from_right) = ends(from_port, from);
+        let (to_left, to_right) = ends(to_port, to);
+
+        return Outline::new()
+            .move_to(from_left)
+            .line_to(to_left)
+            .line_to(to_right)
+            .line_to(from_right)
+            .close();
+    }
+}
+
+impl Default for ContourTile {
+    #[inline]
+    fn default() -> Self {
+        return Self::new(&[0.25, 0.5, 0.75], 0.1);
+    }
+}
+
+impl Tile for ContourTile {
+    /// Uniform brightness has no contours, only corners sampled by [`Tile::configure`] produce isolines.
+    #[inline]
+    fn set_brightness(&mut self, brightness: f32) {
+        self.corners = [brightness; 4];
+    }
+
+    #[inline]
+    fn configure(&mut self, block: &BlockSample) {
+        self.corners = block.corners();
+    }
+
+    #[inline]
+    fn rotate(&mut self) {
+        self.corners.rotate_right(1);
+    }
+
+    #[inline]
+    fn mirror(&mut self) {
+        let [top_left, top_right, bottom_right, bottom_left] = self.corners;
+        self.corners = [top_right, top_left, bottom_left, bottom_right];
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        let orientations: Vec<Orientation> = Orientation::all()
+            .filter(|&orientation| self.clone().oriented(orientation).corners == self.corners)
+            .collect();
+
+        return Symmetry::from_orientations(&orientations);
+    }
+
+    fn edges(&self) -> EdgeSignature {
+        let edges = Side::ALL.map(|side| Edge::new(
+            EdgeColour::BACKGROUND,
+            self.crossings(side)
+                .into_iter()
+                .map(|position| Connection::new(position, 2.0 * self.half_width, EdgeColour::FOREGROUND))
+                .collect()
+        ));
+        let [top, right, bottom, left] = edges;
+
+        return EdgeSignature::new(top, right, bottom, left);
+    }
+
+    fn direction(&self) -> Option<f32> {
+        let [top_left, top_right, bottom_right, bottom_left] = self.corners;
+        let gx = (top_right + bottom_right - top_left - bottom_left) / 2.0;
+        let gy = (bottom_left + bottom_right - top_left - top_right) / 2.0;
+
+        if gx.abs() + gy.abs() < f32::EPSILON {
+            return None;
+        }
+
+        // Isolines are perpendicular to the gradient
+        return Some((gy.atan2(gx) + FRAC_PI_2).rem_euclid(PI));
+    }
+
+    fn outlines(&self) -> Vec<Outline> {
+        return self.segments().into_iter().map(|segment| self.band(segment)).collect();
+    }
+
+    fn links(&self) -> Vec<Link> {
+        return self.segments()
+            .into_iter()
+            .map(|segment| {
+                let [(from, start), (to, end)] = segment;
+                return Link {
+                    from,
+                    to,
+                    centre_line: Outline::new().move_to(start).line_to(end),
+                    bands: vec![self.band(segment)]
+                };
+            })
+            .collect();
+    }
+}
+
+impl ToText for ContourTile {}
+
+impl ToPostScript for ContourTile {}
+
+impl ToSVG for ContourTile {
+    fn to_svg_node(&self) -> Box<dyn Node> {
+        let mut g = Group::new();
+
+        for outline in self.outlines() {
+            g.append(Path::new().set("d", outline.to_path_data()));
+        }
+
+        return Box::new(g);
+    }
+}
+
+#[inline]
+fn point(side: Side, position: f32) -> Vec2<f32> {
+    return match side {
+        Side::Top => Vec2::new(position, 0.0),
+        Side::Right => Vec2::new(1.0, position),
+        Side::Bottom => Vec2::new(position, 1.0),
+        Side::Left => Vec2::new(0.0, position),
+    };
+}
+
+#[inline]
+fn clamp(p: Vec2<f32>) -> Vec2<f32> {
+    return Vec2::new(p.x().clamp(0.0, 1.0), p.y().clamp(0.0, 1.0));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tile::edge::Side;
+
+    use super::ContourTile;
+
+    /// Tile with corners of the marching squares case, corners above the threshold 0.5 have the given value.
+    fn case_tile(case: usize, above: f32) -> ContourTile {
+        let mut tile = ContourTile::new(&[0.5], 0.1);
+        tile.set_corners([8, 4, 2, 1].map(|bit| if case & bit != 0 { above } else { 0.0 }));
+        return tile;
+    }
+
+    #[test]
+    fn every_crossed_side_is_joined_once() {
+        // Saddles are resolved both ways, mean of the corners is below 0.5 for 0.6 and equal to it for 1.0
+        for above in [0.6, 1.0] {
+            for case in 0..16 {
+                let tile = case_tile(case, above);
+                assert_eq!(tile.case(0.5), case);
+
+                let mut joined: Vec<Side> = tile.sides(0.5).iter().flat_map(|&(from, to)| [from, to]).collect();
+                joined.sort_by_key(|side| *side as usize);
+                let crossed: Vec<Side> = Side::ALL.into_iter().filter(|side| tile.crossing(*side, 0.5).is_some()).collect();
+
+                assert_eq!(joined, crossed, "case {}", case);
+            }
+        }
+    }
+
+    #[test]
+    fn saddles_are_resolved_by_centre() {
+        let below = case_tile(5, 0.6).sides(0.5);
+        let above = case_tile(5, 1.0).sides(0.5);
+
+        assert_eq!(below, &[(Side::Top, Side::Right), (Side::Left, Side::Bottom)]);
+        assert_eq!(above, &[(Side::Left, Side::Top), (Side::Bottom, Side::Right)]);
+    }
+}
@@ -0,0 +1,266 @@
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+
+use rand::{seq::SliceRandom, Rng};
+use svg::{node::element::{Circle, Group}, Node};
+
+use crate::{to_svg::ToSVG, to_postscript::ToPostScript, to_text::ToText, utils::format_number, vec2::Vec2, geometry::{Outline, sampled_coverage}};
+
+use super::{traits::{Tile, COVERAGE_SAMPLES}, symmetry::{Orientation, Symmetry}, edge::{EdgeSignature, Edge, EdgeColour}};
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DotArrangement {
+    /// Amplitude modulated halftone, dots on a regular screen grow with darkness.
+    ///
+    /// Screen is the integer vector `(a, b)`, it has `a² + b²` dots per cell and is rotated by `atan2(b, a)`.
+    /// Integer vectors keep the screen seamless across cells.
+    Halftone { screen: (i32, i32) },
+    /// Frequency modulated stippling, dots have fixed radius and their number follows darkness.
+    Stipple { radius: f32, positions: Vec<Vec2<f32>> }
+}
+
+/// Tile of dots following brightness, can be mixed with other tiles through [`super::dynamic::AnyTile`].
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DotTile {
+    arrangement: DotArrangement,
+    darkness: f32
+}
+
+impl DotTile {
+    /// Halftone screen with the given integer vector, see [`DotArrangement::Halftone`].
+    pub fn halftone(a: i32, b: i32) -> Self {
+        let screen = if (a, b) == (0, 0) { (1, 0) } else { (a, b) };
+        return Self { arrangement: DotArrangement::Halftone { screen }, darkness: 0.5 };
+    }
+
+    /// Halftone screen closest to the given number of dot rows per cell and screen angle in degrees.
+    pub fn halftone_angle(frequency: f32, angle: f32) -> Self {
+        let angle = angle.to_radians();
+        return Self::halftone((frequency * angle.cos()).round() as i32, (frequency * angle.sin()).round() as i32);
+    }
+
+    /// Stippling with dots of the given radius, see [`DotArrangement::Stipple`].
+    pub fn stipple(radius: f32) -> Self {
+        let mut tile = Self {
+            arrangement: DotArrangement::Stipple { radius: radius.clamp(0.01, 0.5), positions: Vec::new() },
+            darkness: 0.5
+        };
+        tile.set_brightness(0.5);
+
+        return tile;
+    }
+
+    #[inline]
+    pub fn arrangement(&self) -> &DotArrangement {
+        return &self.arrangement;
+    }
+
+    /// Centres and radii of all dots.
+    pub fn dots(&self) -> Vec<(Vec2<f32>, f32)> {
+        return match &self.arrangement {
+            DotArrangement::Halftone { screen } => halftone_dots(*screen, self.darkness),
+            DotArrangement::Stipple { radius, positions } => positions.iter().map(|&p| (p, *radius)).collect(),
+        };
+    }
+}
+
+impl Default for DotTile {
+    #[inline]
+    fn default() -> Self {
+        return Self::halftone(1, 0);
+    }
+}
+
+impl Tile for DotTile {
+    fn set_brightness(&mut self, brightness: f32) {
+        self.darkness = (1.0 - brightness).clamp(0.0, 1.0);
+
+        if let DotArrangement::Stipple { radius, positions } = &mut self.arrangement {
+            *positions = stipple_positions(*radius, self.darkness);
+        }
+    }
+
+    fn rotate(&mut self) {
+        match &mut self.arrangement {
+            DotArrangement::Halftone { screen } => *screen = (-screen.1, screen.0),
+            DotArrangement::Stipple { positions, .. } => {
+                let orientation = Orientation::new(1, false);
+                positions.iter_mut().for_each(|p| *p = orientation.apply((*p).into()).into());
+            },
+        }
+    }
+
+    fn mirror(&mut self) {
+        match &mut self.arrangement {
+            DotArrangement::Halftone { screen } => *screen = (-screen.0, screen.1),
+            DotArrangement::Stipple { positions, .. } => {
+                positions.iter_mut().for_each(|p| *p = Vec2::new(1.0 - p.x(), p.y()));
+            },
+        }
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        let (a, b) = match self.arrangement {
+            DotArrangement::Halftone { screen } => screen,
+            DotArrangement::Stipple { .. } => return Symmetry::NONE,
+        };
+
+        // Square screen is the same after quarter turns, mirroring changes its angle unless it is axis aligned or diagonal
+        let mirror_symmetric = a == 0 || b == 0 || a.abs() == b.abs();
+        let orientations: Vec<Orientation> = Orientation::all()
+            .filter(|orientation| !orientation.mirrored() || mirror_symmetric)
+            .collect();
+
+        return Symmetry::from_orientations(&orientations);
+    }
+
+    fn edges(&self) -> EdgeSignature {
+        let edge = Edge::solid(EdgeColour::BACKGROUND);
+        return EdgeSignature::new(edge.clone(), edge.clone(), edge.clone(), edge);
+    }
+
+    fn outlines(&self) -> Vec<Outline> {
+        return self.dots()
+            .into_iter()
+            .map(|(centre, radius)| Outline::new()
+                .move_to(centre - Vec2::new(radius, 0.0))
+                .arc_to(radius, false, true, centre + Vec2::new(radius, 0.0))
+                .arc_to(radius, false, true, centre - Vec2::new(radius, 0.0))
+                .close())
+            .collect();
+    }
+
+    fn coverage(&self) -> f32 {
+        let (a, b) = match self.arrangement {
+            DotArrangement::Halftone { screen } => screen,
+            DotArrangement::Stipple { .. } => return sampled_coverage(&self.outlines(), COVERAGE_SAMPLES),
+        };
+
+        // Halftone dots overflow into neighbouring cells, so coverage of a single screen square is measured
+        let spacing = 1.0 / ((a * a + b * b) as f32).sqrt();
+        return screen_coverage(halftone_radius((a, b), self.darkness) / spacing);
+    }
+}
+
+impl ToSVG for DotTile {
+    fn to_svg_node(&self) -> Box<dyn Node> {
+        let mut g = Group::new();
+
+        for (centre, radius) in self.dots() {
+            g.append(Circle::new()
+                .set("cx", centre.x())
+                .set("cy", centre.y())
+                .set("r", radius));
+        }
+
+        return Box::new(g);
+    }
+}
+
+impl ToText for DotTile {}
+
+impl ToPostScript for DotTile {
+    /// Filled circle, takes centre and radius.
+    fn postscript_procedures(&self) -> &'static str {
+        return "/dot { 0 360 arc f } bind def";
+    }
+
+    fn to_postscript(&self, origin: Vec2<f32>) -> String {
+        return self.dots()
+            .into_iter()
+            .map(|(centre, radius)| {
+                let centre = centre + origin;
+                return format!("{} {} {} dot\n", format_number(centre.x()), format_number(centre.y()), format_number(radius));
+            })
+            .collect();
+    }
+}
+
+/// Dots of the screen centred inside of the cell, dots near sides overflow into neighbouring cells
+/// exactly where their screens would continue.
+fn halftone_dots((a, b): (i32, i32), darkness: f32) -> Vec<(Vec2<f32>, f32)> {
+    let norm = (a * a + b * b) as f32;
+    let u = Vec2::new(a as f32, b as f32) * (1.0 / norm);
+    let v = Vec2::new(-b as f32, a as f32) * (1.0 / norm);
+    let offset = (u + v) * 0.5;
+
+    let radius = halftone_radius((a, b), darkness);
+    if radius <= 0.0 {
+        return Vec::new();
+    }
+
+    let range = a.abs() + b.abs();
+    let mut dots = Vec::new();
+    for i in -range..=range {
+        for j in -range..=range {
+            let centre = u * i as f32 + v * j as f32 + offset;
+            if (0.0..1.0).contains(&centre.x()) && (0.0..1.0).contains(&centre.y()) {
+                dots.push((centre, radius));
+            }
+        }
+    }
+
+    return dots;
+}
+
+/// Radius of dots covering `darkness` of the area, dots merge with neighbours in dark tones.
+fn halftone_radius((a, b): (i32, i32), darkness: f32) -> f32 {
+    if darkness <= 0.0 {
+        return 0.0;
+    }
+
+    let spacing = 1.0 / ((a * a + b * b) as f32).sqrt();
+
+    // Coverage grows monotonically until dots touch diagonally and fill the whole screen square
+    let (mut low, mut high) = (0.0, FRAC_1_SQRT_2);
+    for _ in 0..24 {
+        let middle = (low + high) / 2.0;
+        if screen_coverage(middle) < darkness {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    return high * spacing;
+}
+
+/// Fraction of the square screen cell covered by the dot, radius is relative to the cell side.
+fn screen_coverage(radius: f32) -> f32 {
+    let mut area = PI * radius * radius;
+    if radius > 0.5 {
+        let segment = radius * radius * (0.5 / radius).acos() - 0.5 * (radius * radius - 0.25).sqrt();
+        area -= 4.0 * segment;
+    }
+
+    return area.clamp(0.0, 1.0);
+}
+
+/// Jittered random positions of dots covering `darkness` of the cell, dots stay inside of the cell.
+fn stipple_positions(radius: f32, darkness: f32) -> Vec<Vec2<f32>> {
+    let max_per_side = ((1.0 / (2.0 * radius)).floor() as usize).max(1);
+    let count = ((darkness / (PI * radius * radius)).round() as usize).min(max_per_side * max_per_side);
+    if count == 0 {
+        return Vec::new();
+    }
+
+    // Every dot gets its own stratum of the cell, so dots are spread evenly
+    let per_side = ((count as f32).sqrt().ceil() as usize).min(max_per_side);
+    let mut strata: Vec<usize> = (0..per_side * per_side).collect();
+    let mut rng = rand::thread_rng();
+    strata.shuffle(&mut rng);
+
+    let stratum_size = 1.0 / per_side as f32;
+    return strata.into_iter()
+        .take(count)
+        .map(|stratum| {
+            let mut jitter = |index: usize| -> f32 {
+                let position = (index as f32 + rng.gen::<f32>()) * stratum_size;
+                return position.clamp(radius, 1.0 - radius);
+            };
+            let x = jitter(stratum % per_side);
+            return Vec2::new(x, jitter(stratum / per_side));
+        })
+        .collect();
+}
@@ -0,0 +1,245 @@
+use svg::Node;
+
+use crate::{vec2::Vec2, to_svg::ToSVG, to_postscript::ToPostScript, to_text::ToText, geometry::Outline, image::BlockSample};
+
+use super::{traits::Tile, symmetry::Symmetry, edge::{EdgeSignature, Link}, region::Region};
+
+/// Object safe counterpart of [`Tile`], implemented for every tile which can be rendered to svg, PostScript and text.
+pub trait DynTile {
+    fn set_brightness(&mut self, brightness: f32);
+    fn configure(&mut self, block: &BlockSample);
+    fn rotate(&mut self);
+    fn mirror(&mut self);
+    fn symmetry(&self) -> Symmetry;
+    fn edges(&self) -> EdgeSignature;
+    fn direction(&self) -> Option<f32>;
+    fn outlines(&self) -> Vec<Outline>;
+    fn coverage(&self) -> f32;
+    fn links(&self) -> Vec<Link>;
+    fn regions(&self) -> Vec<Region>;
+    fn to_svg_node(&self) -> Box<dyn Node>;
+    fn to_text(&self) -> [char; 2];
+    fn postscript_procedures(&self) -> &'static str;
+    fn to_postscript(&self, origin: Vec2<f32>) -> String;
+    fn clone_box(&self) -> Box<dyn DynTile>;
+}
+
+impl<TTile: Tile + ToSVG + ToText + ToPostScript + 'static> DynTile for TTile {
+    #[inline]
+    fn set_brightness(&mut self, brightness: f32) {
+        Tile::set_brightness(self, brightness);
+    }
+
+    #[inline]
+    fn configure(&mut self, block: &BlockSample) {
+        Tile::configure(self, block);
+    }
+
+    #[inline]
+    fn rotate(&mut self) {
+        Tile::rotate(self);
+    }
+
+    #[inline]
+    fn mirror(&mut self) {
+        Tile::mirror(self);
+    }
+
+    #[inline]
+    fn symmetry(&self) -> Symmetry {
+        return Tile::symmetry(self);
+    }
+
+    #[inline]
+    fn edges(&self) -> EdgeSignature {
+        return Tile::edges(self);
+    }
+
+    #[inline]
+    fn direction(&self) -> Option<f32> {
+        return Tile::direction(self);
+    }
+
+    #[inline]
+    fn outlines(&self) -> Vec<Outline> {
+        return Tile::outlines(self);
+    }
+
+    #[inline]
+    fn coverage(&self) -> f32 {
+        return Tile::coverage(self);
+    }
+
+    #[inline]
+    fn links(&self) -> Vec<Link> {
+        return Tile::links(self);
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        return Tile::regions(self);
+    }
+
+    #[inline]
+    fn to_svg_node(&self) -> Box<dyn Node> {
+        return ToSVG::to_svg_node(self);
+    }
+
+    #[inline]
+    fn to_text(&self) -> [char; 2] {
+        return ToText::to_text(self);
+    }
+
+    #[inline]
+    fn postscript_procedures(&self) -> &'static str {
+        return ToPostScript::postscript_procedures(self);
+    }
+
+    #[inline]
+    fn to_postscript(&self, origin: Vec2<f32>) -> String {
+        return ToPostScript::to_postscript(self, origin);
+    }
+
+    #[inline]
+    fn clone_box(&self) -> Box<dyn DynTile> {
+        return Box::new(self.clone());
+    }
+}
+
+/// Tile of any type, allows to mix different tiles in one generator at the cost of dynamic dispatch.
+///
+/// Type of the boxed tile is erased, so the tile can't be serialized with `serde` feature.
+///
+/// ```
+/// use truchet::{generator::PatternGenerator, vec2::Vec2, tile::{dynamic::AnyTile, triangle::ElasticTriangleTile, circle::ElasticCircleTile}};
+///
+/// let generator = PatternGenerator::from_slice([
+///     [AnyTile::new(ElasticTriangleTile::type_a()), AnyTile::new(ElasticCircleTile::default())],
+///     [AnyTile::new(ElasticCircleTile::default()), AnyTile::new(ElasticTriangleTile::type_c())]
+/// ], Vec2::new(4, 4));
+/// ```
+pub struct AnyTile(Box<dyn DynTile>);
+
+impl AnyTile {
+    pub fn new<TTile: Tile + ToSVG + ToText + ToPostScript + 'static>(tile: TTile) -> Self {
+        return Self(Box::new(tile));
+    }
+
+    pub fn from_box(tile: Box<dyn DynTile>) -> Self {
+        return Self(tile);
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &dyn DynTile {
+        return self.0.as_ref();
+    }
+}
+
+impl Clone for AnyTile {
+    #[inline]
+    fn clone(&self) -> Self {
+        return Self(self.0.clone_box());
+    }
+}
+
+impl Tile for AnyTile {
+    #[inline]
+    fn set_brightness(&mut self, brightness: f32) {
+        self.0.set_brightness(brightness);
+    }
+
+    #[inline]
+    fn configure(&mut self, block: &BlockSample) {
+        self.0.configure(block);
+    }
+
+    #[inline]
+    fn rotate(&mut self) {
+        self.0.rotate();
+    }
+
+    #[inline]
+    fn mirror(&mut self) {
+        self.0.mirror();
+    }
+
+    #[inline]
+    fn symmetry(&self) -> Symmetry {
+        return self.0.symmetry();
+    }
+
+    #[inline]
+    fn edges(&self) -> EdgeSignature {
+        return self.0.edges();
+    }
+
+    #[inline]
+    fn direction(&self) -> Option<f32> {
+        return self.0.direction();
+    }
+
+    #[inline]
+    fn outlines(&self) -> Vec<Outline> {
+        return self.0.outlines();
+    }
+
+    #[inline]
+    fn coverage(&self) -> f32 {
+        return self.0.coverage();
+    }
+
+    #[inline]
+    fn links(&self) -> Vec<Link> {
+        return self.0.links();
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        return self.0.regions();
+    }
+}
+
+impl ToText for AnyTile {
+    #[inline]
+    fn to_text(&self) -> [char; 2] {
+        return self.0.to_text();
+    }
+}
+
+impl ToPostScript for AnyTile {
+    #[inline]
+    fn postscript_procedures(&self) -> &'static str {
+        return self.0.postscript_procedures();
+    }
+
+    #[inline]
+    fn to_postscript(&self, origin: Vec2<f32>) -> String {
+        return self.0.to_postscript(origin);
+    }
+}
+
+impl ToSVG for AnyTile {
+    #[inline]
+    fn to_svg_node(&self) -> Box<dyn Node> {
+        return self.0.to_svg_node();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tile::{traits::Tile, circle::ElasticCircleTile}, to_postscript::ToPostScript, to_text::ToText, vec2::Vec2};
+
+    use super::AnyTile;
+
+    #[test]
+    fn text_and_postscript_are_forwarded_to_the_boxed_tile() {
+        let mut rotated = ElasticCircleTile::default();
+        rotated.rotate();
+
+        for tile in [ElasticCircleTile::default(), rotated] {
+            let any = AnyTile::new(tile);
+
+            assert_eq!(any.to_text(), tile.to_text());
+            assert_eq!(any.postscript_procedures(), tile.postscript_procedures());
+            assert_eq!(any.to_postscript(Vec2::new(2.0, 3.0)), tile.to_postscript(Vec2::new(2.0, 3.0)));
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::geometry::Outline;
+
+use super::symmetry::Orientation;
+
+const POSITION_EPSILON: f32 = 1e-4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left
+}
+
+impl Side {
+    pub const ALL: [Side; 4] = [Side::Top, Side::Right, Side::Bottom, Side::Left];
+
+    pub fn opposite(&self) -> Side {
+        return match self {
+            Side::Top => Side::Bottom,
+            Side::Right => Side::Left,
+            Side::Bottom => Side::Top,
+            Side::Left => Side::Right,
+        };
+    }
+}
+
+/// Colour label of the tile motif along a side, tiles may use any number of colours.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeColour(pub u8);
+
+impl EdgeColour {
+    pub const BACKGROUND: EdgeColour = EdgeColour(0);
+    pub const FOREGROUND: EdgeColour = EdgeColour(1);
+}
+
+/// Curve or band of the motif crossing tile side.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Connection {
+    position: f32,
+    width: f32,
+    colour: EdgeColour
+}
+
+impl Connection {
+    pub const fn new(position: f32, width: f32, colour: EdgeColour) -> Self {
+        return Self { position, width, colour };
+    }
+
+    /// Position of connection centre along the side.
+    #[inline]
+    pub fn position(&self) -> f32 {
+        return self.position;
+    }
+
+    #[inline]
+    pub fn width(&self) -> f32 {
+        return self.width;
+    }
+
+    #[inline]
+    pub fn colour(&self) -> EdgeColour {
+        return self.colour;
+    }
+}
+
+/// Describes how tile motif touches one side of the cell.
+///
+/// Positions along `Top` and `Bottom` sides are measured by `x` and along `Left` and `Right` by `y`,
+/// so touching sides of neighbouring tiles use the same coordinates.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edge {
+    colour: EdgeColour,
+    connections: Vec<Connection>
+}
+
+impl Edge {
+    pub fn new(colour: EdgeColour, mut connections: Vec<Connection>) -> Self {
+        connections.sort_by(|a, b| a.position.total_cmp(&b.position));
+        return Self { colour, connections };
+    }
+
+    /// Side filled with a single colour.
+    pub fn solid(colour: EdgeColour) -> Self {
+        return Self::new(colour, Vec::new());
+    }
+
+    /// Colour of the side outside of connections.
+    #[inline]
+    pub fn colour(&self) -> EdgeColour {
+        return self.colour;
+    }
+
+    #[inline]
+    pub fn connections(&self) -> &[Connection] {
+        return &self.connections;
+    }
+
+    /// Same edge with positions measured from the opposite end of the side.
+    pub fn reversed(&self) -> Self {
+        let connections = self.connections.iter()
+            .map(|c| Connection::new(1.0 - c.position, c.width, c.colour))
+            .collect();
+
+        return Self::new(self.colour, connections);
+    }
+
+    /// Checks whether motif continues across the shared side, connection widths are ignored
+    /// as they usually depend on brightness.
+    pub fn matches(&self, other: &Edge) -> bool {
+        return self.colour == other.colour &&
+            self.connections.len() == other.connections.len() &&
+            self.connections.iter()
+                .zip(other.connections.iter())
+                .all(|(a, b)| a.colour == b.colour && (a.position - b.position).abs() < POSITION_EPSILON);
+    }
+}
+
+/// Connection on the tile side, `connection` is index in [`Edge::connections`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Port {
+    pub side: Side,
+    pub connection: usize
+}
+
+impl Port {
+    pub const fn new(side: Side, connection: usize) -> Self {
+        return Self { side, connection };
+    }
+}
+
+/// Piece of a strand which goes through the tile between two connections.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Link {
+    pub from: Port,
+    pub to: Port,
+    /// Open path along the middle of the strand from `from` to `to` connection.
+    pub centre_line: Outline,
+    /// Filled shapes drawing this piece of the strand.
+    pub bands: Vec<Outline>
+}
+
+/// Edges of all four tile sides.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeSignature {
+    top: Edge,
+    right: Edge,
+    bottom: Edge,
+    left: Edge
+}
+
+impl EdgeSignature {
+    pub fn new(top: Edge, right: Edge, bottom: Edge, left: Edge) -> Self {
+        return Self { top, right, bottom, left };
+    }
+
+    pub fn side(&self, side: Side) -> &Edge {
+        return match side {
+            Side::Top => &self.top,
+            Side::Right => &self.right,
+            Side::Bottom => &self.bottom,
+            Side::Left => &self.left,
+        };
+    }
+
+    /// Signature of the tile transformed by the given orientation.
+    pub fn oriented(&self, orientation: Orientation) -> EdgeSignature {
+        let mut oriented = self.clone();
+
+        if orientation.mirrored() {
+            oriented = Self::new(oriented.top.reversed(), oriented.left, oriented.bottom.reversed(), oriented.right);
+        }
+
+        for _ in 0..orientation.quarter_turns() {
+            oriented = Self::new(oriented.left.reversed(), oriented.top, oriented.right.reversed(), oriented.bottom);
+        }
+
+        return oriented;
+    }
+
+    /// Checks whether tile with this signature can be placed next to tile with `other` signature
+    /// which lies on the given `side`.
+    pub fn matches(&self, side: Side, other: &EdgeSignature) -> bool {
+        return self.side(side).matches(other.side(side.opposite()));
+    }
+}
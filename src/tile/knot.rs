@@ -0,0 +1,235 @@
+use std::f32::consts::FRAC_PI_2;
+
+use svg::{node::element::{Group, Path}, Node};
+
+use crate::{to_svg::ToSVG, to_postscript::{ToPostScript, outline_postscript}, to_text::ToText, vec2::Vec2, geometry::Outline};
+
+use super::{traits::Tile, circle::ElasticCircleTile, symmetry::{Orientation, Symmetry}, edge::{EdgeSignature, Edge, EdgeColour, Connection, Link, Port, Side}, region::Region};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KnotTileType {
+    /// Horizontal and vertical strands crossing in the middle of the cell, strand below is interrupted by a gap.
+    Crossing { horizontal_over: bool },
+    /// Two arcs turning strands around opposite corners, the same as [`ElasticCircleTile`].
+    Arcs { flipped: bool }
+}
+
+/// Tile of knotwork, strands enter the cell in the middle of every side.
+///
+/// Any layout of knot tiles forms continuous strands, [`KnotTile::alternate`] makes strands go over and under in turn.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KnotTile {
+    tile_type: KnotTileType,
+    half_width: f32,
+    gap: f32
+}
+
+impl KnotTile {
+    pub fn new(tile_type: KnotTileType, width: f32, gap: f32) -> Self {
+        return Self { tile_type, half_width: width.clamp(0.0, 1.0) / 2.0, gap: gap.max(0.0) };
+    }
+
+    pub fn crossing() -> Self {
+        return Self::new(KnotTileType::Crossing { horizontal_over: true }, 0.2, 0.05);
+    }
+
+    pub fn arcs() -> Self {
+        return Self::new(KnotTileType::Arcs { flipped: false }, 0.2, 0.05);
+    }
+
+    #[inline]
+    pub fn tile_type(&self) -> KnotTileType {
+        return self.tile_type;
+    }
+
+    /// Sets which strand of the crossing goes over so that every strand of the grid alternates.
+    ///
+    /// Regions between strands always contain cell corners and are coloured as a checkerboard of the corners,
+    /// so the top left corner parity decides the crossing.
+    pub fn alternate(&mut self, row: usize, col: usize) {
+        if let KnotTileType::Crossing { .. } = self.tile_type {
+            self.tile_type = KnotTileType::Crossing { horizontal_over: (row + col).is_multiple_of(2) };
+        }
+    }
+
+    #[inline]
+    fn arcs_tile(&self, flipped: bool) -> ElasticCircleTile {
+        return ElasticCircleTile::new(flipped, 2.0 * self.half_width, 0.5);
+    }
+
+    fn band(&self, from: f32, to: f32, horizontal: bool) -> Outline {
+        let (low, high) = (0.5 - self.half_width, 0.5 + self.half_width);
+        let point = |along: f32, across: f32| if horizontal { Vec2::new(along, across) } else { Vec2::new(across, along) };
+
+        return Outline::new()
+            .move_to(point(from, low))
+            .line_to(point(to, low))
+            .line_to(point(to, high))
+            .line_to(point(from, high))
+            .close();
+    }
+}
+
+impl Default for KnotTile {
+    #[inline]
+    fn default() -> Self {
+        return Self::crossing();
+    }
+}
+
+impl Tile for KnotTile {
+    #[inline]
+    fn set_brightness(&mut self, brightness: f32) {
+        self.half_width = (1.0 - brightness) / 4.0;
+    }
+
+    fn rotate(&mut self) {
+        self.tile_type = match self.tile_type {
+            KnotTileType::Crossing { horizontal_over } => KnotTileType::Crossing { horizontal_over: !horizontal_over },
+            KnotTileType::Arcs { flipped } => KnotTileType::Arcs { flipped: !flipped },
+        };
+    }
+
+    fn mirror(&mut self) {
+        if let KnotTileType::Arcs { flipped } = self.tile_type {
+            self.tile_type = KnotTileType::Arcs { flipped: !flipped };
+        }
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        return match self.tile_type {
+            KnotTileType::Crossing { .. } => Symmetry::from_orientations(&[
+                Orientation::new(2, false),
+                Orientation::new(0, true),
+                Orientation::new(2, true)
+            ]),
+            KnotTileType::Arcs { flipped } => self.arcs_tile(flipped).symmetry(),
+        };
+    }
+
+    fn edges(&self) -> EdgeSignature {
+        let edge = Edge::new(EdgeColour::BACKGROUND, vec![
+            Connection::new(0.5, 2.0 * self.half_width, EdgeColour::FOREGROUND)
+        ]);
+
+        return EdgeSignature::new(edge.clone(), edge.clone(), edge.clone(), edge);
+    }
+
+    fn direction(&self) -> Option<f32> {
+        return match self.tile_type {
+            KnotTileType::Crossing { horizontal_over: true } => Some(0.0),
+            KnotTileType::Crossing { horizontal_over: false } => Some(FRAC_PI_2),
+            KnotTileType::Arcs { flipped } => self.arcs_tile(flipped).direction(),
+        };
+    }
+
+    fn outlines(&self) -> Vec<Outline> {
+        let horizontal_over = match self.tile_type {
+            KnotTileType::Crossing { horizontal_over } => horizontal_over,
+            KnotTileType::Arcs { flipped } => return self.arcs_tile(flipped).outlines(),
+        };
+
+        let gap_start = 0.5 - self.half_width - self.gap;
+        let gap_end = 0.5 + self.half_width + self.gap;
+
+        return vec![
+            self.band(0.0, 1.0, horizontal_over),
+            self.band(0.0, gap_start, !horizontal_over),
+            self.band(gap_end, 1.0, !horizontal_over)
+        ];
+    }
+
+    fn links(&self) -> Vec<Link> {
+        let horizontal_over = match self.tile_type {
+            KnotTileType::Crossing { horizontal_over } => horizontal_over,
+            KnotTileType::Arcs { flipped } => return self.arcs_tile(flipped).links(),
+        };
+
+        let mut outlines = self.outlines().into_iter();
+        let over = outlines.next().into_iter().collect();
+        let under = outlines.collect();
+        let (horizontal_bands, vertical_bands) = if horizontal_over { (over, under) } else { (under, over) };
+
+        return vec![
+            Link {
+                from: Port::new(Side::Left, 0),
+                to: Port::new(Side::Right, 0),
+                centre_line: Outline::new().move_to(Vec2::new(0.0, 0.5)).line_to(Vec2::new(1.0, 0.5)),
+                bands: horizontal_bands
+            },
+            Link {
+                from: Port::new(Side::Top, 0),
+                to: Port::new(Side::Bottom, 0),
+                centre_line: Outline::new().move_to(Vec2::new(0.5, 0.0)).line_to(Vec2::new(0.5, 1.0)),
+                bands: vertical_bands
+            }
+        ];
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        if let KnotTileType::Arcs { flipped } = self.tile_type {
+            return self.arcs_tile(flipped).regions();
+        }
+
+        // Crossing strands split the cell into quarters
+        let quarter = |x: usize, y: usize| {
+            let origin = Vec2::new(x as f32, y as f32) * 0.5;
+            let outline = Outline::new()
+                .move_to(origin)
+                .line_to(origin + Vec2::new(0.5, 0.0))
+                .line_to(origin + Vec2::new(0.5, 0.5))
+                .line_to(origin + Vec2::new(0.0, 0.5))
+                .close();
+
+            return Region::new(Vec2::new(x, y), outline);
+        };
+
+        return vec![quarter(0, 0), quarter(1, 0), quarter(1, 1), quarter(0, 1)];
+    }
+}
+
+impl ToPostScript for KnotTile {
+    fn postscript_procedures(&self) -> &'static str {
+        return match self.tile_type {
+            KnotTileType::Crossing { .. } => "",
+            KnotTileType::Arcs { flipped } => self.arcs_tile(flipped).postscript_procedures(),
+        };
+    }
+
+    fn to_postscript(&self, origin: Vec2<f32>) -> String {
+        return match self.tile_type {
+            KnotTileType::Crossing { .. } => self.outlines()
+                .iter()
+                .map(|outline| outline_postscript(outline.transformed(1.0, origin).segments()))
+                .collect(),
+            KnotTileType::Arcs { flipped } => self.arcs_tile(flipped).to_postscript(origin),
+        };
+    }
+}
+
+impl ToText for KnotTile {
+    fn to_text(&self) -> [char; 2] {
+        return match self.tile_type {
+            KnotTileType::Crossing { horizontal_over: true } => ['─', '┿'],
+            KnotTileType::Crossing { horizontal_over: false } => ['─', '╂'],
+            KnotTileType::Arcs { flipped } => self.arcs_tile(flipped).to_text(),
+        };
+    }
+}
+
+impl ToSVG for KnotTile {
+    fn to_svg_node(&self) -> Box<dyn Node> {
+        let mut g = Group::new();
+
+        for outline in self.outlines() {
+            g.append(Path::new()
+                .set("stroke", "black")
+                .set("stroke-width", "0.01")
+                .set("d", outline.to_path_data()));
+        }
+
+        return Box::new(g);
+    }
+}
@@ -0,0 +1,110 @@
+use svg::Node;
+
+use crate::{to_svg::ToSVG, to_postscript::ToPostScript, to_text::ToText, vec2::Vec2, geometry::Outline, image::BlockSample};
+
+use super::{traits::Tile, symmetry::Symmetry, edge::{EdgeSignature, Link}, region::Region};
+
+/// Function which sets parameters of the tile from the sampled source image block.
+pub type BlockMapping<TTile> = fn(&mut TTile, &BlockSample);
+
+/// Tile configured by custom mapping, allows tile parameters to follow several properties of the image block.
+///
+/// Mapping is a function pointer, so the tile can't be serialized with `serde` feature.
+#[derive(Clone)]
+pub struct MappedTile<TTile: Tile> {
+    tile: TTile,
+    mapping: BlockMapping<TTile>
+}
+
+impl<TTile: Tile> MappedTile<TTile> {
+    pub fn new(tile: TTile, mapping: BlockMapping<TTile>) -> Self {
+        return Self { tile, mapping };
+    }
+
+    #[inline]
+    pub fn tile(&self) -> &TTile {
+        return &self.tile;
+    }
+}
+
+impl<TTile: Tile> Tile for MappedTile<TTile> {
+    #[inline]
+    fn set_brightness(&mut self, brightness: f32) {
+        (self.mapping)(&mut self.tile, &BlockSample::new(brightness));
+    }
+
+    #[inline]
+    fn configure(&mut self, block: &BlockSample) {
+        (self.mapping)(&mut self.tile, block);
+    }
+
+    #[inline]
+    fn rotate(&mut self) {
+        self.tile.rotate();
+    }
+
+    #[inline]
+    fn mirror(&mut self) {
+        self.tile.mirror();
+    }
+
+    #[inline]
+    fn symmetry(&self) -> Symmetry {
+        return self.tile.symmetry();
+    }
+
+    #[inline]
+    fn edges(&self) -> EdgeSignature {
+        return self.tile.edges();
+    }
+
+    #[inline]
+    fn direction(&self) -> Option<f32> {
+        return self.tile.direction();
+    }
+
+    #[inline]
+    fn outlines(&self) -> Vec<Outline> {
+        return self.tile.outlines();
+    }
+
+    #[inline]
+    fn coverage(&self) -> f32 {
+        return self.tile.coverage();
+    }
+
+    #[inline]
+    fn links(&self) -> Vec<Link> {
+        return self.tile.links();
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        return self.tile.regions();
+    }
+}
+
+impl<TTile: ToText> ToText for MappedTile<TTile> {
+    #[inline]
+    fn to_text(&self) -> [char; 2] {
+        return self.tile.to_text();
+    }
+}
+
+impl<TTile: ToPostScript> ToPostScript for MappedTile<TTile> {
+    #[inline]
+    fn postscript_procedures(&self) -> &'static str {
+        return self.tile.postscript_procedures();
+    }
+
+    #[inline]
+    fn to_postscript(&self, origin: Vec2<f32>) -> String {
+        return self.tile.to_postscript(origin);
+    }
+}
+
+impl<TTile: Tile + ToSVG> ToSVG for MappedTile<TTile> {
+    #[inline]
+    fn to_svg_node(&self) -> Box<dyn Node> {
+        return self.tile.to_svg_node();
+    }
+}
@@ -1,3 +1,14 @@
 pub mod traits;
+pub mod symmetry;
+pub mod edge;
 pub mod triangle;
 pub mod circle;
+pub mod template;
+pub mod dynamic;
+pub mod transfer;
+pub mod mapped;
+pub mod knot;
+pub mod region;
+pub mod contour;
+pub mod dot;
+pub mod placed;
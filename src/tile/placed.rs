@@ -0,0 +1,57 @@
+use svg::{node::element::Group, Node};
+
+use crate::{to_svg::ToSVG, vec2::Vec2, geometry::Outline};
+
+use super::traits::Tile;
+
+/// Tile together with its place in the grid, tiles are unit squares and the grid starts at the origin.
+#[derive(Debug)]
+pub struct PlacedTile<'a, TTile> {
+    pub row: usize,
+    pub col: usize,
+    pub tile: &'a TTile
+}
+
+impl<'a, TTile> PlacedTile<'a, TTile> {
+    #[inline]
+    pub fn new(row: usize, col: usize, tile: &'a TTile) -> Self {
+        return Self { row, col, tile };
+    }
+
+    /// Top left corner of the tile, `x` follows columns and `y` follows rows.
+    #[inline]
+    pub fn origin(&self) -> Vec2<f32> {
+        return Vec2::new(self.col as f32, self.row as f32);
+    }
+
+    /// SVG transform which moves the tile drawn in the unit square to its place.
+    pub fn svg_transform(&self) -> String {
+        return format!("translate({} {})", self.col, self.row);
+    }
+}
+
+// Tile is only borrowed, so copies don't need the tile to be copyable
+impl<TTile> Clone for PlacedTile<'_, TTile> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<TTile> Copy for PlacedTile<'_, TTile> {}
+
+impl<TTile: Tile> PlacedTile<'_, TTile> {
+    /// Outlines of the tile moved to its place in the grid.
+    pub fn outlines(&self) -> Vec<Outline> {
+        let origin = self.origin();
+        return self.tile.outlines().into_iter().map(|outline| outline.transformed(1.0, origin)).collect();
+    }
+}
+
+impl<TTile: ToSVG> ToSVG for PlacedTile<'_, TTile> {
+    fn to_svg_node(&self) -> Box<dyn Node> {
+        return Box::new(Group::new()
+            .set("transform", self.svg_transform())
+            .add(self.tile.to_svg_node()));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{vec2::Vec2, geometry::Outline};
+
+use super::symmetry::Orientation;
+
+/// Part of the cell background bounded by strands of the tile.
+///
+/// Every region contains a corner of the cell, so regions of a whole grid can be
+/// two-coloured by parity of the corners, like a checkerboard.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Region {
+    /// Corner of the cell, `x` is the column offset and `y` is the row offset.
+    pub corner: Vec2<usize>,
+    pub outline: Outline
+}
+
+impl Region {
+    #[inline]
+    pub fn new(corner: Vec2<usize>, outline: Outline) -> Self {
+        return Self { corner, outline };
+    }
+
+    pub fn oriented(&self, orientation: Orientation) -> Self {
+        let (x, y) = orientation.apply((self.corner.x() as f32, self.corner.y() as f32));
+        return Self::new(Vec2::new(x.round() as usize, y.round() as usize), self.outline.oriented(orientation));
+    }
+
+    /// Colour index of the region of the tile placed at the given row and column.
+    #[inline]
+    pub fn parity(&self, row: usize, col: usize) -> usize {
+        return (row + self.corner.y() + col + self.corner.x()) % 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tile::{circle::ElasticCircleTile, traits::Tile}, vec2::Vec2};
+
+    #[test]
+    fn regions_contain_their_corners() {
+        for tile in [ElasticCircleTile::new(false, 0.1, 0.5), ElasticCircleTile::new(true, 0.1, 0.5)] {
+            for region in tile.regions() {
+                let corner = Vec2::new(region.corner.x() as f32, region.corner.y() as f32);
+                let inside = corner * 0.96 + Vec2::new(0.02, 0.02);
+
+                assert!(region.outline.contains(inside), "region of corner {:?} does not contain it", region.corner);
+            }
+        }
+    }
+
+    #[test]
+    fn regions_across_strands_have_different_parity() {
+        let regions = ElasticCircleTile::new(false, 0.1, 0.5).regions();
+
+        for (row, col) in [(0, 0), (0, 1), (3, 6)] {
+            let [upper, lower, middle] = [0, 1, 2].map(|i| regions[i].parity(row, col));
+
+            assert_eq!(upper, lower);
+            assert_ne!(upper, middle);
+            assert_eq!(upper, (row + col) % 2);
+        }
+    }
+
+    #[test]
+    fn regions_meeting_at_grid_point_share_parity() {
+        // Regions of the four tiles around the point at row 1 and column 1
+        let tile = ElasticCircleTile::new(false, 0.1, 0.5);
+        let flipped = ElasticCircleTile::new(true, 0.1, 0.5);
+        let placed = [(0, 0, &tile), (0, 1, &flipped), (1, 0, &flipped), (1, 1, &tile)];
+
+        let parities: Vec<usize> = placed.iter()
+            .flat_map(|(row, col, tile)| tile.regions()
+                .into_iter()
+                .filter(move |region| (row + region.corner.y(), col + region.corner.x()) == (1, 1))
+                .map(move |region| region.parity(*row, *col)))
+            .collect();
+
+        assert_eq!(parities.len(), 4);
+        assert!(parities.iter().all(|parity| *parity == parities[0]));
+    }
+}
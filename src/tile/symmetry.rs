@@ -0,0 +1,125 @@
+/// Element of the symmetry group of a square (dihedral group D4).
+///
+/// Orientation is applied to a tile as an optional mirror across the vertical axis
+/// followed by `quarter_turns` clockwise rotations by 90 degrees.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Orientation {
+    quarter_turns: u8,
+    mirrored: bool
+}
+
+impl Orientation {
+    pub const IDENTITY: Orientation = Orientation::new(0, false);
+
+    pub const fn new(quarter_turns: u8, mirrored: bool) -> Self {
+        return Self { quarter_turns: quarter_turns % 4, mirrored };
+    }
+
+    /// All 8 orientations of a square.
+    pub fn all() -> impl Iterator<Item = Orientation> {
+        return (0..8).map(Self::from_index);
+    }
+
+    #[inline]
+    pub fn quarter_turns(&self) -> u8 {
+        return self.quarter_turns;
+    }
+
+    #[inline]
+    pub fn mirrored(&self) -> bool {
+        return self.mirrored;
+    }
+
+    /// Orientation equal to applying `self` first and `other` after it.
+    pub fn then(self, other: Orientation) -> Orientation {
+        let quarter_turns = if other.mirrored {
+            4 + other.quarter_turns - self.quarter_turns
+        } else {
+            self.quarter_turns + other.quarter_turns
+        };
+
+        return Orientation::new(quarter_turns, self.mirrored != other.mirrored);
+    }
+
+    pub fn inverse(self) -> Orientation {
+        if self.mirrored {
+            return self;
+        }
+
+        return Orientation::new(4 - self.quarter_turns, false);
+    }
+
+    /// Maps point in the unit tile space (`y` pointing down) according to orientation.
+    pub fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let (mut x, mut y) = if self.mirrored { (1.0 - x, y) } else { (x, y) };
+
+        for _ in 0..self.quarter_turns {
+            (x, y) = (1.0 - y, x);
+        }
+
+        return (x, y);
+    }
+
+    #[inline]
+    fn index(&self) -> u8 {
+        return self.mirrored as u8 * 4 + self.quarter_turns;
+    }
+
+    #[inline]
+    fn from_index(index: u8) -> Self {
+        return Self::new(index % 4, index >= 4);
+    }
+}
+
+/// Subgroup of orientations which leave tile motif unchanged.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Symmetry(u8);
+
+impl Symmetry {
+    /// Tile has no symmetries, every orientation produces different motif.
+    pub const NONE: Symmetry = Symmetry(1);
+    /// Tile looks the same in every orientation.
+    pub const FULL: Symmetry = Symmetry(u8::MAX);
+
+    pub fn from_orientations(orientations: &[Orientation]) -> Self {
+        let mut symmetry = Self::NONE;
+
+        for orientation in orientations {
+            symmetry.0 |= 1 << orientation.index();
+        }
+
+        return symmetry;
+    }
+
+    #[inline]
+    pub fn contains(&self, orientation: Orientation) -> bool {
+        return self.0 & (1 << orientation.index()) != 0;
+    }
+
+    /// Number of orientations in the group.
+    #[inline]
+    pub fn order(&self) -> usize {
+        return self.0.count_ones() as usize;
+    }
+
+    /// Orientations producing pairwise different motifs.
+    pub fn distinct_orientations(&self) -> Vec<Orientation> {
+        let mut distinct: Vec<Orientation> = Vec::with_capacity(8 / self.order());
+
+        for orientation in Orientation::all() {
+            if distinct.iter().all(|other| !self.contains(orientation.then(other.inverse()))) {
+                distinct.push(orientation);
+            }
+        }
+
+        return distinct;
+    }
+}
+
+impl Default for Symmetry {
+    #[inline]
+    fn default() -> Self {
+        return Self::NONE;
+    }
+}
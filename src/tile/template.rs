@@ -0,0 +1,507 @@
+use std::{fmt::Display, mem::discriminant, sync::Arc};
+#[cfg(feature = "serde")]
+use std::cell::RefCell;
+
+use svg::{node::element::{Group, Path, path::{Data, Command, Parameters, Position}}, Node};
+
+use crate::{to_svg::ToSVG, to_postscript::ToPostScript, to_text::ToText, geometry::Outline};
+
+use super::{traits::Tile, symmetry::{Orientation, Symmetry}, edge::{EdgeSignature, Edge, EdgeColour}};
+
+#[derive(Debug)]
+pub enum TemplateError {
+    Parse(svg::parser::Error),
+    Io(std::io::Error),
+    /// Template needs at least two keyframes to interpolate between.
+    NotEnoughKeyframes,
+    /// Path of the keyframe has commands which differ from the first keyframe.
+    IncompatibleKeyframe(usize),
+    /// Keyframe path has no valid `data-brightness` attribute.
+    MissingBrightness(usize)
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            TemplateError::Parse(err) => write!(f, "failed to parse path data: {}", err),
+            TemplateError::Io(err) => write!(f, "failed to read svg: {}", err),
+            TemplateError::NotEnoughKeyframes => write!(f, "template needs at least two keyframes"),
+            TemplateError::IncompatibleKeyframe(index) => write!(f, "keyframe {} has commands incompatible with the first keyframe", index),
+            TemplateError::MissingBrightness(index) => write!(f, "keyframe {} has no valid brightness", index),
+        };
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Tile shape described by several keyframe paths, shape for any brightness is interpolated
+/// between the nearest keyframes.
+///
+/// Paths are drawn in the unit square and must have the same sequence of commands with the same positions, number of parameters
+/// and arc flags, e.g. "dark" and "light" versions of the same motif.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TileTemplateData"))]
+pub struct TileTemplate {
+    // Sorted by brightness
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_keyframes"))]
+    keyframes: Vec<(f32, Vec<Command>)>,
+    edges: EdgeSignature
+}
+
+/// Deserialized template before its keyframes are parsed.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TileTemplateData {
+    keyframes: Vec<(f32, String)>,
+    edges: EdgeSignature
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TileTemplateData> for TileTemplate {
+    type Error = TemplateError;
+
+    fn try_from(data: TileTemplateData) -> Result<Self, Self::Error> {
+        let keyframes: Vec<_> = data.keyframes.iter().map(|(brightness, path)| (*brightness, path.as_str())).collect();
+        return Ok(Self::new(&keyframes)?.with_edges(data.edges));
+    }
+}
+
+/// Keyframes are stored as path data, the same way they are given to [`TileTemplate::new`].
+#[cfg(feature = "serde")]
+fn serialize_keyframes<S: serde::Serializer>(keyframes: &[(f32, Vec<Command>)], serializer: S) -> Result<S::Ok, S::Error> {
+    return serializer.collect_seq(keyframes.iter().map(|(brightness, commands)| {
+        return (*brightness, svg::node::Value::from(Data::from(commands.clone())).to_string());
+    }));
+}
+
+impl TileTemplate {
+    /// Creates template from `(brightness, path data)` keyframes.
+    pub fn new(keyframes: &[(f32, &str)]) -> Result<Self, TemplateError> {
+        let mut parsed = Vec::with_capacity(keyframes.len());
+
+        for (brightness, path) in keyframes {
+            let data = Data::parse(path).map_err(TemplateError::Parse)?;
+            parsed.push((*brightness, data.to_vec()));
+        }
+
+        if parsed.len() < 2 {
+            return Err(TemplateError::NotEnoughKeyframes);
+        }
+
+        for (index, (_, commands)) in parsed.iter().enumerate().skip(1) {
+            if !Self::compatible(&parsed[0].1, commands) {
+                return Err(TemplateError::IncompatibleKeyframe(index));
+            }
+        }
+
+        parsed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let background = Edge::solid(EdgeColour::BACKGROUND);
+
+        return Ok(Self {
+            keyframes: parsed,
+            edges: EdgeSignature::new(background.clone(), background.clone(), background.clone(), background)
+        });
+    }
+
+    /// Creates template from all `path` elements of svg document, each path must have `data-brightness` attribute.
+    pub fn from_svg(content: &str) -> Result<Self, TemplateError> {
+        let mut keyframes = Vec::new();
+
+        for event in svg::read(content).map_err(TemplateError::Io)? {
+            match event {
+                svg::parser::Event::Tag("path", _, attributes) => {
+                    let brightness = attributes.get("data-brightness")
+                        .and_then(|value| value.parse::<f32>().ok())
+                        .ok_or(TemplateError::MissingBrightness(keyframes.len()))?;
+                    let path = attributes.get("d").map(|value| value.to_string()).unwrap_or_default();
+                    keyframes.push((brightness, path));
+                },
+                svg::parser::Event::Error(err) => return Err(TemplateError::Parse(err)),
+                _ => {}
+            }
+        }
+
+        let keyframes: Vec<_> = keyframes.iter().map(|(brightness, path)| (*brightness, path.as_str())).collect();
+        return Self::new(&keyframes);
+    }
+
+    /// Describes how template motif touches sides of the cell, template has no edge connections by default.
+    pub fn with_edges(mut self, edges: EdgeSignature) -> Self {
+        self.edges = edges;
+        return self;
+    }
+
+    #[inline]
+    pub fn edges(&self) -> &EdgeSignature {
+        return &self.edges;
+    }
+
+    /// Path data interpolated for the given brightness.
+    pub fn shape(&self, brightness: f32) -> Data {
+        let upper = self.keyframes
+            .partition_point(|(level, _)| *level < brightness)
+            .clamp(1, self.keyframes.len() - 1);
+        let (lower_brightness, lower) = &self.keyframes[upper - 1];
+        let (upper_brightness, upper) = &self.keyframes[upper];
+
+        let range = upper_brightness - lower_brightness;
+        let t = if range > f32::EPSILON {
+            ((brightness - lower_brightness) / range).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let mut data = Data::new();
+        for (from, to) in lower.iter().zip(upper.iter()) {
+            data.append(Self::interpolate_command(from, to, t));
+        }
+
+        return data;
+    }
+
+    /// Outline of the shape interpolated for the given brightness.
+    pub fn outline(&self, brightness: f32) -> Outline {
+        return Outline::from_path_commands(&self.shape(brightness));
+    }
+
+    fn compatible(a: &[Command], b: &[Command]) -> bool {
+        return a.len() == b.len() && a.iter().zip(b.iter()).all(|(a_command, b_command)| {
+            return discriminant(a_command) == discriminant(b_command) && match (Self::arguments(a_command), Self::arguments(b_command)) {
+                (Some((a_position, a)), Some((b_position, b))) => a_position == b_position && a.len() == b.len() && a.iter()
+                    .zip(b.iter())
+                    .enumerate()
+                    .all(|(index, (a, b))| !Self::is_flag(a_command, index) || a == b),
+                _ => true
+            };
+        });
+    }
+
+    fn arguments(command: &Command) -> Option<(Position, &Parameters)> {
+        return match command {
+            Command::Move(position, parameters) |
+            Command::Line(position, parameters) |
+            Command::HorizontalLine(position, parameters) |
+            Command::VerticalLine(position, parameters) |
+            Command::QuadraticCurve(position, parameters) |
+            Command::SmoothQuadraticCurve(position, parameters) |
+            Command::CubicCurve(position, parameters) |
+            Command::SmoothCubicCurve(position, parameters) |
+            Command::EllipticalArc(position, parameters) => Some((*position, parameters)),
+            Command::Close => None,
+        };
+    }
+
+    /// Large arc and sweep flags of every arc, they can't be interpolated.
+    fn is_flag(command: &Command, index: usize) -> bool {
+        return matches!(command, Command::EllipticalArc(..)) && matches!(index % 7, 3 | 4);
+    }
+
+    fn interpolate_command(from: &Command, to: &Command, t: f32) -> Command {
+        let (Some((_, a)), Some((_, b))) = (Self::arguments(from), Self::arguments(to)) else {
+            return from.clone();
+        };
+
+        let parameters: Parameters = a.iter()
+            .zip(b.iter())
+            .enumerate()
+            .map(|(index, (a, b))| if Self::is_flag(from, index) { *a } else { a + (b - a) * t })
+            .collect::<Vec<_>>()
+            .into();
+
+        return match from {
+            Command::Move(position, _) => Command::Move(*position, parameters),
+            Command::Line(position, _) => Command::Line(*position, parameters),
+            Command::HorizontalLine(position, _) => Command::HorizontalLine(*position, parameters),
+            Command::VerticalLine(position, _) => Command::VerticalLine(*position, parameters),
+            Command::QuadraticCurve(position, _) => Command::QuadraticCurve(*position, parameters),
+            Command::SmoothQuadraticCurve(position, _) => Command::SmoothQuadraticCurve(*position, parameters),
+            Command::CubicCurve(position, _) => Command::CubicCurve(*position, parameters),
+            Command::SmoothCubicCurve(position, _) => Command::SmoothCubicCurve(*position, parameters),
+            Command::EllipticalArc(position, _) => Command::EllipticalArc(*position, parameters),
+            Command::Close => Command::Close,
+        };
+    }
+}
+
+/// Tile which renders shape of the template interpolated by brightness, template is shared by all clones of the tile.
+///
+/// With `serde` feature tiles of serialized images and patterns store every template once, the following tiles
+/// refer to it by index and share it again when deserialized. Tile serialized on its own stores a copy of the template.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemplateTile {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_template", deserialize_with = "deserialize_template"))]
+    template: Option<Arc<TileTemplate>>,
+    brightness: f32,
+    orientation: Orientation
+}
+
+#[cfg(feature = "serde")]
+thread_local! {
+    // Templates stored by the tiles serialized or deserialized so far, `None` outside of tiles
+    static SHARED_TEMPLATES: RefCell<Option<Vec<Arc<TileTemplate>>>> = const { RefCell::new(None) };
+}
+
+/// Template stored by the tile, or index of the template already stored by one of the previous tiles.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum SharedTemplate {
+    Index(usize),
+    Template(Arc<TileTemplate>)
+}
+
+/// Ends the outermost scope of shared templates, even when serialization panics.
+#[cfg(feature = "serde")]
+struct SharedTemplatesScope(bool);
+
+#[cfg(feature = "serde")]
+impl Drop for SharedTemplatesScope {
+    fn drop(&mut self) {
+        if self.0 {
+            SHARED_TEMPLATES.with(|shared| *shared.borrow_mut() = None);
+        }
+    }
+}
+
+/// Runs `f` with templates shared between tiles, nested scopes share templates of the outer one.
+#[cfg(feature = "serde")]
+fn with_shared_templates<R>(f: impl FnOnce() -> R) -> R {
+    let _scope = SHARED_TEMPLATES.with(|shared| {
+        let mut shared = shared.borrow_mut();
+        let outermost = shared.is_none();
+        if outermost {
+            *shared = Some(Vec::new());
+        }
+
+        return SharedTemplatesScope(outermost);
+    });
+
+    return f();
+}
+
+/// Serializes tiles storing every template of [`TemplateTile`]s once.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_tiles<S: serde::Serializer, T: serde::Serialize>(tiles: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    return with_shared_templates(|| tiles.serialize(serializer));
+}
+
+/// Deserializes tiles written by [`serialize_tiles`], tiles with the same template share it.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_tiles<'de, D: serde::Deserializer<'de>, T: serde::Deserialize<'de>>(deserializer: D) -> Result<T, D::Error> {
+    return with_shared_templates(|| T::deserialize(deserializer));
+}
+
+#[cfg(feature = "serde")]
+fn serialize_template<S: serde::Serializer>(template: &Option<Arc<TileTemplate>>, serializer: S) -> Result<S::Ok, S::Error> {
+    let shared = template.as_ref().map(|template| SHARED_TEMPLATES.with(|shared| {
+        let mut shared = shared.borrow_mut();
+        let Some(shared) = shared.as_mut() else {
+            return SharedTemplate::Template(template.clone());
+        };
+
+        if let Some(index) = shared.iter().position(|stored| Arc::ptr_eq(stored, template)) {
+            return SharedTemplate::Index(index);
+        }
+
+        shared.push(template.clone());
+        return SharedTemplate::Template(template.clone());
+    }));
+
+    return serde::Serialize::serialize(&shared, serializer);
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_template<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Arc<TileTemplate>>, D::Error> {
+    use serde::de::Error;
+
+    let Some(shared) = <Option<SharedTemplate> as serde::Deserialize>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    return SHARED_TEMPLATES.with(|stored| {
+        let mut stored = stored.borrow_mut();
+
+        return match (shared, stored.as_mut()) {
+            (SharedTemplate::Template(template), Some(stored)) => {
+                stored.push(template.clone());
+                Ok(Some(template))
+            },
+            (SharedTemplate::Template(template), None) => Ok(Some(template)),
+            (SharedTemplate::Index(index), stored) => stored.and_then(|stored| stored.get(index).cloned())
+                .map(Some)
+                .ok_or_else(|| D::Error::custom(format!("template {} is not stored by any of the previous tiles", index))),
+        };
+    });
+}
+
+impl TemplateTile {
+    pub fn new(template: Arc<TileTemplate>) -> Self {
+        return Self { template: Some(template), brightness: 0.5, orientation: Orientation::IDENTITY };
+    }
+
+    #[inline]
+    pub fn brightness(&self) -> f32 {
+        return self.brightness;
+    }
+
+    #[inline]
+    pub fn orientation(&self) -> Orientation {
+        return self.orientation;
+    }
+}
+
+impl Tile for TemplateTile {
+    #[inline]
+    fn set_brightness(&mut self, brightness: f32) {
+        self.brightness = brightness;
+    }
+
+    #[inline]
+    fn rotate(&mut self) {
+        self.orientation = self.orientation.then(Orientation::new(1, false));
+    }
+
+    #[inline]
+    fn mirror(&mut self) {
+        self.orientation = self.orientation.then(Orientation::new(0, true));
+    }
+
+    #[inline]
+    fn symmetry(&self) -> Symmetry {
+        return Symmetry::NONE;
+    }
+
+    fn outlines(&self) -> Vec<Outline> {
+        return match &self.template {
+            Some(template) => vec![template.outline(self.brightness).oriented(self.orientation)],
+            None => Vec::new(),
+        };
+    }
+
+    fn edges(&self) -> EdgeSignature {
+        let background = Edge::solid(EdgeColour::BACKGROUND);
+
+        return match &self.template {
+            Some(template) => template.edges().oriented(self.orientation),
+            None => EdgeSignature::new(background.clone(), background.clone(), background.clone(), background),
+        };
+    }
+}
+
+impl ToText for TemplateTile {}
+
+impl ToPostScript for TemplateTile {}
+
+impl ToSVG for TemplateTile {
+    fn to_svg_node(&self) -> Box<dyn Node> {
+        let mut g = Group::new();
+
+        if let Some(template) = &self.template {
+            g.append(Path::new().set("d", template.shape(self.brightness)));
+        }
+
+        if self.orientation != Orientation::IDENTITY {
+            // Transforms are applied from right to left
+            let mirror = if self.orientation.mirrored() { " matrix(-1 0 0 1 1 0)" } else { "" };
+            g.assign("transform", format!("rotate({}, 0.5, 0.5){}", 90 * self.orientation.quarter_turns() as u32, mirror));
+        }
+
+        return Box::new(g);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use svg::node::element::path::{Command, Position};
+
+    use super::{TileTemplate, TemplateError};
+
+    #[test]
+    fn arcs_are_interpolated_without_flags() {
+        let template = TileTemplate::new(&[(0.0, "M 0 0 A 0.5 0.5 0 1 1 1 1"), (1.0, "M 0 0 A 0.25 0.25 0 1 1 1 0.5")]).unwrap();
+        let shape = template.shape(0.5);
+        let Command::EllipticalArc(_, parameters) = &shape[1] else {
+            panic!("second command should be an arc");
+        };
+        assert_eq!(parameters.to_vec(), vec![0.375, 0.375, 0.0, 1.0, 1.0, 1.0, 0.75]);
+
+        let from = Command::EllipticalArc(Position::Absolute, vec![0.5, 0.5, 0.0, 0.0, 1.0, 1.0, 1.0].into());
+        let to = Command::EllipticalArc(Position::Absolute, vec![0.25, 0.25, 90.0, 1.0, 0.0, 1.0, 0.5].into());
+        let Command::EllipticalArc(_, parameters) = TileTemplate::interpolate_command(&from, &to, 0.5) else {
+            panic!("interpolated command should be an arc");
+        };
+        assert_eq!(parameters.to_vec(), vec![0.375, 0.375, 45.0, 0.0, 1.0, 1.0, 0.75]);
+    }
+
+    #[test]
+    fn keyframes_with_different_arc_flags_or_positions_are_incompatible() {
+        let flags = TileTemplate::new(&[(0.0, "M 0 0 A 0.5 0.5 0 0 1 1 1"), (1.0, "M 0 0 A 0.5 0.5 0 1 1 1 1")]);
+        assert!(matches!(flags, Err(TemplateError::IncompatibleKeyframe(1))));
+
+        let positions = TileTemplate::new(&[(0.0, "M 0 0 A 0.5 0.5 0 0 1 1 1"), (1.0, "M 0 0 a 0.5 0.5 0 0 1 1 1")]);
+        assert!(matches!(positions, Err(TemplateError::IncompatibleKeyframe(1))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn template_tile_round_trips_through_json() {
+        use std::sync::Arc;
+
+        use crate::{tile::traits::Tile, to_svg::ToSVG};
+        use super::TemplateTile;
+
+        let edges = crate::tile::triangle::ElasticTriangleTile::type_a().edges();
+        let template = TileTemplate::new(&[(0.0, "M 0 0 L 1 0 L 0 1 Z"), (1.0, "M 0 0 L 0.5 0 L 0 0.5 Z")]).unwrap().with_edges(edges);
+        let mut tile = TemplateTile::new(Arc::new(template));
+        tile.set_brightness(0.3);
+        tile.rotate();
+
+        let json = serde_json::to_string(&tile).unwrap();
+        let restored: TemplateTile = serde_json::from_str(&json).unwrap();
+
+        assert!(json.contains(r#"[0.0,"M0,0 L1,0 L0,1 z"]"#), "{}", json);
+        assert_eq!(restored.edges(), tile.edges());
+        assert_eq!(restored.to_svg_node().to_string(), tile.to_svg_node().to_string());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn image_stores_template_once_and_shares_it_when_deserialized() {
+        use std::sync::Arc;
+
+        use crate::{generator::PatternGenerator, image::fixtures::Uniform, truchet_image::{generate, TruchetImage}, to_svg::ToSVG, vec2::Vec2};
+        use super::TemplateTile;
+
+        let template = Arc::new(TileTemplate::new(&[(0.0, "M 0 0 L 1 0 L 0 1 Z"), (1.0, "M 0 0 L 0.5 0 L 0 0.5 Z")]).unwrap());
+        let tile = TemplateTile::new(template);
+        let pattern = PatternGenerator::from_slice([[tile.clone(), tile.clone()], [tile.clone(), tile]], Vec2::new(2, 2));
+        let image = generate(&Uniform(Vec2::new(8, 8), 0.5), pattern);
+
+        let json = serde_json::to_string(&image).unwrap();
+        let restored: TruchetImage<PatternGenerator<TemplateTile>> = serde_json::from_str(&json).unwrap();
+
+        // Once for the pattern and once for the laid out tiles
+        assert_eq!(json.matches("keyframes").count(), 2, "{}", json);
+        let first = restored.get(0, 0).unwrap().template.as_ref().unwrap();
+        assert!(restored.tiles().all(|placed| Arc::ptr_eq(placed.tile.template.as_ref().unwrap(), first)));
+        assert_eq!(restored.to_svg_node().to_string(), image.to_svg_node().to_string());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn undefined_template_index_is_rejected() {
+        use std::sync::Arc;
+
+        use crate::{generator::PatternGenerator, vec2::Vec2};
+        use super::TemplateTile;
+
+        let tile = TemplateTile::new(Arc::new(TileTemplate::new(&[(0.0, "M 0 0 L 1 0 L 0 1 Z"), (1.0, "M 0 0 L 0.5 0 L 0 0.5 Z")]).unwrap()));
+        let mut value = serde_json::to_value(PatternGenerator::from_slice([[tile]], Vec2::new(1, 1))).unwrap();
+        value["tiles"][0]["template"] = 0.into();
+
+        let error = serde_json::from_value::<PatternGenerator<TemplateTile>>(value).err().unwrap();
+        assert!(error.to_string().contains("template 0 is not stored"), "{}", error);
+    }
+}
@@ -1,3 +1,84 @@
-pub trait Tile: Default + Clone + Copy {
+use crate::{geometry::{Outline, sampled_coverage}, image::BlockSample};
+
+use super::{symmetry::{Orientation, Symmetry}, edge::{EdgeSignature, Link}, region::Region};
+
+/// Number of samples along each side of the cell used to measure coverage of tiles without exact formula.
+pub const COVERAGE_SAMPLES: usize = 64;
+
+pub trait Tile: Clone {
     fn set_brightness(&mut self, brightness: f32);
+
+    /// Adjusts tile to the sampled source image block, by default only brightness is used.
+    fn configure(&mut self, block: &BlockSample) {
+        self.set_brightness(block.brightness());
+    }
+
+    /// Rotates tile by 90 degrees clockwise.
+    fn rotate(&mut self);
+
+    /// Mirrors tile across its vertical axis.
+    fn mirror(&mut self);
+
+    /// Orientations which leave tile unchanged.
+    fn symmetry(&self) -> Symmetry;
+
+    /// Describes where and how tile motif touches sides of the cell.
+    fn edges(&self) -> EdgeSignature;
+
+    /// Direction of the dominant line of the motif in radians, measured clockwise from `x` axis as `y` axis points down.
+    /// Tiles without such line return `None`.
+    fn direction(&self) -> Option<f32> {
+        return None;
+    }
+
+    /// Filled shapes of the motif in the unit cell, every outline is rendered as separate svg path.
+    fn outlines(&self) -> Vec<Outline> {
+        return Vec::new();
+    }
+
+    /// Strand pieces which connect side connections inside of the tile, tiles without strands return nothing.
+    fn links(&self) -> Vec<Link> {
+        return Vec::new();
+    }
+
+    /// Background regions between strands, used for two-colour filling.
+    fn regions(&self) -> Vec<Region> {
+        return Vec::new();
+    }
+
+    /// Fraction of the cell area covered by the motif, sampled from outlines unless tile knows exact value.
+    fn coverage(&self) -> f32 {
+        return sampled_coverage(&self.outlines(), COVERAGE_SAMPLES);
+    }
+
+    fn rotated(mut self) -> Self {
+        self.rotate();
+        return self;
+    }
+
+    fn mirrored(mut self) -> Self {
+        self.mirror();
+        return self;
+    }
+
+    fn oriented(mut self, orientation: Orientation) -> Self {
+        if orientation.mirrored() {
+            self.mirror();
+        }
+
+        for _ in 0..orientation.quarter_turns() {
+            self.rotate();
+        }
+
+        return self;
+    }
+
+    /// All distinct orientations of the tile.
+    fn orientations(&self) -> Vec<Self> {
+        return self.symmetry()
+            .distinct_orientations()
+            .into_iter()
+            .map(|orientation| self.clone().oriented(orientation))
+            .collect();
+    }
 }
@@ -0,0 +1,279 @@
+use std::sync::Arc;
+
+use svg::Node;
+
+use crate::{to_svg::ToSVG, to_postscript::ToPostScript, to_text::ToText, vec2::Vec2, geometry::Outline, image::BlockSample};
+
+use super::{traits::Tile, symmetry::Symmetry, edge::{EdgeSignature, Link}, region::Region};
+
+/// Maps source image brightness to brightness passed to tile.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransferCurve {
+    #[default]
+    Linear,
+    /// `brightness ^ gamma`
+    Gamma(f32),
+    /// Linear interpolation between `(input, output)` control points, sorted by input.
+    Piecewise(Vec<(f32, f32)>),
+    /// Outputs for evenly spaced inputs from 0 to 1, linearly interpolated.
+    Lookup(Vec<f32>)
+}
+
+impl TransferCurve {
+    /// Creates curve from `(input, output)` control points.
+    pub fn piecewise(mut points: Vec<(f32, f32)>) -> Self {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        return Self::Piecewise(points);
+    }
+
+    pub fn apply(&self, brightness: f32) -> f32 {
+        let brightness = brightness.clamp(0.0, 1.0);
+
+        return match self {
+            TransferCurve::Linear => brightness,
+            TransferCurve::Gamma(gamma) => brightness.powf(*gamma),
+            TransferCurve::Piecewise(points) => {
+                let upper = points.partition_point(|(input, _)| *input < brightness);
+
+                match (upper.checked_sub(1).and_then(|lower| points.get(lower)), points.get(upper)) {
+                    (Some(&(x0, y0)), Some(&(x1, y1))) => if x1 - x0 > f32::EPSILON {
+                        y0 + (y1 - y0) * (brightness - x0) / (x1 - x0)
+                    } else {
+                        y0
+                    },
+                    (Some(&(_, y)), None) | (None, Some(&(_, y))) => y,
+                    (None, None) => brightness
+                }
+            },
+            TransferCurve::Lookup(table) => {
+                if table.len() < 2 {
+                    return table.first().copied().unwrap_or(brightness);
+                }
+
+                let position = brightness * (table.len() - 1) as f32;
+                let lower = (position.floor() as usize).min(table.len() - 2);
+                let t = position - lower as f32;
+                table[lower] + (table[lower + 1] - table[lower]) * t
+            },
+        };
+    }
+
+    /// Builds curve which makes rendered tone of the tile match source brightness.
+    ///
+    /// Tile is sampled at `samples` brightness values and `coverage` is measured for each,
+    /// i.e. fraction of the cell area filled by the motif. Then the brightness-vs-tone curve is inverted numerically.
+    /// Tones the tile cannot reproduce are clamped to the nearest reachable one.
+    pub fn calibrate<TTile: Tile>(tile: &TTile, coverage: impl Fn(&TTile) -> f32, samples: usize) -> Self {
+        let samples = samples.max(2);
+        let inputs: Vec<f32> = (0..samples).map(|i| i as f32 / (samples - 1) as f32).collect();
+        let rendered: Vec<f32> = inputs.iter()
+            .map(|brightness| {
+                let mut tile = tile.clone();
+                tile.set_brightness(*brightness);
+                return 1.0 - coverage(&tile);
+            })
+            .collect();
+
+        let (darkest, _) = rendered.iter().enumerate().min_by(|a, b| a.1.total_cmp(b.1)).unwrap();
+        let (lightest, _) = rendered.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).unwrap();
+
+        let table = inputs.iter()
+            .map(|target| {
+                let segment = rendered.windows(2).position(|pair| {
+                    return (pair[0] <= *target && *target <= pair[1]) || (pair[1] <= *target && *target <= pair[0]);
+                });
+
+                return match segment {
+                    Some(i) if (rendered[i + 1] - rendered[i]).abs() > f32::EPSILON => {
+                        let t = (target - rendered[i]) / (rendered[i + 1] - rendered[i]);
+                        inputs[i] + (inputs[i + 1] - inputs[i]) * t
+                    },
+                    Some(i) => inputs[i],
+                    None if *target < rendered[darkest] => inputs[darkest],
+                    None => inputs[lightest],
+                };
+            })
+            .collect();
+
+        return Self::Lookup(table);
+    }
+}
+
+/// Tile which passes brightness through transfer curve before applying it to the wrapped tile.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransferTile<TTile: Tile> {
+    tile: TTile,
+    curve: Arc<TransferCurve>
+}
+
+impl<TTile: Tile> TransferTile<TTile> {
+    pub fn new(tile: TTile, curve: Arc<TransferCurve>) -> Self {
+        return Self { tile, curve };
+    }
+
+    #[inline]
+    pub fn tile(&self) -> &TTile {
+        return &self.tile;
+    }
+
+    #[inline]
+    pub fn curve(&self) -> &TransferCurve {
+        return &self.curve;
+    }
+}
+
+impl<TTile: Tile> Tile for TransferTile<TTile> {
+    #[inline]
+    fn set_brightness(&mut self, brightness: f32) {
+        self.tile.set_brightness(self.curve.apply(brightness));
+    }
+
+    #[inline]
+    fn configure(&mut self, block: &BlockSample) {
+        self.tile.configure(&block.with_brightness(self.curve.apply(block.brightness())));
+    }
+
+    #[inline]
+    fn rotate(&mut self) {
+        self.tile.rotate();
+    }
+
+    #[inline]
+    fn mirror(&mut self) {
+        self.tile.mirror();
+    }
+
+    #[inline]
+    fn symmetry(&self) -> Symmetry {
+        return self.tile.symmetry();
+    }
+
+    #[inline]
+    fn edges(&self) -> EdgeSignature {
+        return self.tile.edges();
+    }
+
+    #[inline]
+    fn direction(&self) -> Option<f32> {
+        return self.tile.direction();
+    }
+
+    #[inline]
+    fn outlines(&self) -> Vec<Outline> {
+        return self.tile.outlines();
+    }
+
+    #[inline]
+    fn coverage(&self) -> f32 {
+        return self.tile.coverage();
+    }
+
+    #[inline]
+    fn links(&self) -> Vec<Link> {
+        return self.tile.links();
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        return self.tile.regions();
+    }
+}
+
+impl<TTile: ToText> ToText for TransferTile<TTile> {
+    #[inline]
+    fn to_text(&self) -> [char; 2] {
+        return self.tile.to_text();
+    }
+}
+
+impl<TTile: ToPostScript> ToPostScript for TransferTile<TTile> {
+    #[inline]
+    fn postscript_procedures(&self) -> &'static str {
+        return self.tile.postscript_procedures();
+    }
+
+    #[inline]
+    fn to_postscript(&self, origin: Vec2<f32>) -> String {
+        return self.tile.to_postscript(origin);
+    }
+}
+
+impl<TTile: Tile + ToSVG> ToSVG for TransferTile<TTile> {
+    #[inline]
+    fn to_svg_node(&self) -> Box<dyn Node> {
+        return self.tile.to_svg_node();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tile::circle::ElasticCircleTile;
+
+    use super::TransferCurve;
+
+    fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+        assert!((actual - expected).abs() < tolerance, "{} is not {}", actual, expected);
+    }
+
+    /// Brightness the tile was set to, recovered from the width of its bands.
+    fn brightness(tile: &ElasticCircleTile) -> f32 {
+        return 1.0 - 2.0 * tile.width();
+    }
+
+    #[test]
+    fn samples_are_interpolated() {
+        let piecewise = TransferCurve::piecewise(vec![(1.0, 1.0), (0.0, 0.0), (0.5, 0.8)]);
+        assert_close(piecewise.apply(0.25), 0.4, 1e-6);
+        assert_close(piecewise.apply(0.5), 0.8, 1e-6);
+        assert_close(piecewise.apply(0.75), 0.9, 1e-6);
+
+        let lookup = TransferCurve::Lookup(vec![0.0, 1.0, 0.5]);
+        assert_close(lookup.apply(0.25), 0.5, 1e-6);
+        assert_close(lookup.apply(0.5), 1.0, 1e-6);
+        assert_close(lookup.apply(0.75), 0.75, 1e-6);
+    }
+
+    #[test]
+    fn input_is_clamped_to_unit_range() {
+        let curves = [
+            TransferCurve::Linear,
+            TransferCurve::Gamma(2.2),
+            TransferCurve::piecewise(vec![(0.0, 0.1), (1.0, 0.9)]),
+            TransferCurve::Lookup(vec![0.2, 0.4, 0.7])
+        ];
+
+        for curve in curves {
+            assert_eq!(curve.apply(-0.5), curve.apply(0.0));
+            assert_eq!(curve.apply(1.5), curve.apply(1.0));
+        }
+
+        // Outputs of the outermost control points are kept outside of them
+        let narrow = TransferCurve::piecewise(vec![(0.2, 0.3), (0.8, 0.6)]);
+        assert_close(narrow.apply(0.1), 0.3, 1e-6);
+        assert_close(narrow.apply(0.9), 0.6, 1e-6);
+    }
+
+    #[test]
+    fn calibration_inverts_coverage() {
+        // Rendered tone is the square of the brightness
+        let curve = TransferCurve::calibrate(&ElasticCircleTile::default(), |tile| 1.0 - brightness(tile).powi(2), 65);
+
+        for i in 0..=10 {
+            let target = i as f32 / 10.0;
+            assert_close(curve.apply(target).powi(2), target, 1e-2);
+        }
+    }
+
+    #[test]
+    fn calibration_clamps_unreachable_tones() {
+        // Tile renders tones from 0.2 to 0.8 only
+        let curve = TransferCurve::calibrate(&ElasticCircleTile::default(), |tile| 0.8 - 0.6 * brightness(tile), 17);
+
+        assert_close(curve.apply(0.0), 0.0, 1e-6);
+        assert_close(curve.apply(0.5), 0.5, 1e-2);
+        assert_close(curve.apply(1.0), 1.0, 1e-6);
+        assert_close(curve.apply(0.1), 0.0, 1e-6);
+        assert_close(curve.apply(0.9), 1.0, 1e-6);
+    }
+}
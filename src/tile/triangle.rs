@@ -1,11 +1,14 @@
+use std::f32::consts::{FRAC_PI_4, PI};
+
 use rand::{prelude::Distribution, distributions::Standard};
 use svg::{node::element::Polygon, Node};
 
-use crate::{to_svg::ToSVG, vec2::Vec2};
+use crate::{to_svg::ToSVG, to_postscript::ToPostScript, to_text::ToText, vec2::Vec2, geometry::Outline, utils::format_number};
 
-use super::traits::Tile;
+use super::{traits::Tile, symmetry::{Orientation, Symmetry}, edge::{EdgeSignature, Edge, EdgeColour}};
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ElasticTileType {
     ///
     /// ```
@@ -41,6 +44,28 @@ pub enum ElasticTileType {
     D
 }
 
+impl ElasticTileType {
+    /// Type obtained by rotating tile by 90 degrees clockwise.
+    pub fn rotated(&self) -> Self {
+        return match self {
+            ElasticTileType::A => ElasticTileType::B,
+            ElasticTileType::B => ElasticTileType::C,
+            ElasticTileType::C => ElasticTileType::D,
+            ElasticTileType::D => ElasticTileType::A,
+        };
+    }
+
+    /// Type obtained by mirroring tile across its vertical axis.
+    pub fn mirrored(&self) -> Self {
+        return match self {
+            ElasticTileType::A => ElasticTileType::D,
+            ElasticTileType::B => ElasticTileType::C,
+            ElasticTileType::C => ElasticTileType::B,
+            ElasticTileType::D => ElasticTileType::A,
+        };
+    }
+}
+
 impl ToString for ElasticTileType {
     fn to_string(&self) -> String {
         return match self {
@@ -53,6 +78,7 @@ impl ToString for ElasticTileType {
 }
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ElasticTriangleTile {
     t: f32,
     tile_type: ElasticTileType
@@ -79,6 +105,21 @@ impl ElasticTriangleTile {
         return Self::new(0.5, ElasticTileType::D);
     }
 
+    #[inline]
+    pub fn tile_type(&self) -> ElasticTileType {
+        return self.tile_type;
+    }
+
+    /// Corners of the cell covered by the triangle.
+    fn corners(&self) -> ([f32; 3], [f32; 3]) {
+        return match self.tile_type {
+            ElasticTileType::A => ([1_f32, 0_f32, 0_f32], [1_f32, 1_f32, 0_f32]),
+            ElasticTileType::B => ([0_f32, 0_f32, 1_f32], [1_f32, 0_f32, 0_f32]),
+            ElasticTileType::C => ([0_f32, 1_f32, 1_f32], [0_f32, 0_f32, 1_f32]),
+            ElasticTileType::D => ([1_f32, 1_f32, 0_f32], [0_f32, 1_f32, 1_f32])
+        };
+    }
+
     fn point(&self) -> Vec2<f32> {
         return  match self.tile_type {
             ElasticTileType::A => Vec2::new(1.0 - (0.5  * self.t + 0.25),  0.5 * self.t + 0.25),
@@ -106,16 +147,69 @@ impl Tile for ElasticTriangleTile {
             self.t = 2.0 * brightness - 0.5;
         }
     }
+
+    #[inline]
+    fn rotate(&mut self) {
+        self.tile_type = self.tile_type.rotated();
+    }
+
+    #[inline]
+    fn mirror(&mut self) {
+        self.tile_type = self.tile_type.mirrored();
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        // Triangle is symmetric about the diagonal going through its right angle
+        let reflection = match self.tile_type {
+            ElasticTileType::A | ElasticTileType::C => Orientation::new(1, true),
+            ElasticTileType::B | ElasticTileType::D => Orientation::new(3, true),
+        };
+
+        return Symmetry::from_orientations(&[reflection]);
+    }
+
+    fn edges(&self) -> EdgeSignature {
+        let (top, right, bottom, left) = match self.tile_type {
+            ElasticTileType::A => (false, false, true, true),
+            ElasticTileType::B => (true, false, false, true),
+            ElasticTileType::C => (true, true, false, false),
+            ElasticTileType::D => (false, true, true, false),
+        };
+
+        let edge = |filled: bool| Edge::solid(if filled { EdgeColour::FOREGROUND } else { EdgeColour::BACKGROUND });
+
+        return EdgeSignature::new(edge(top), edge(right), edge(bottom), edge(left));
+    }
+
+    fn outlines(&self) -> Vec<Outline> {
+        let (x, y) = self.corners();
+        let mut outline = Outline::new().move_to(Vec2::new(x[0], y[0]));
+
+        for i in 1..x.len() {
+            outline = outline.line_to(Vec2::new(x[i], y[i]));
+        }
+
+        return vec![outline.line_to(self.point()).close()];
+    }
+
+    #[inline]
+    fn coverage(&self) -> f32 {
+        // Apex moves linearly along the diagonal, so does the area
+        return 0.75 - 0.5 * self.t;
+    }
+
+    fn direction(&self) -> Option<f32> {
+        return match self.tile_type {
+            ElasticTileType::A | ElasticTileType::C => Some(FRAC_PI_4),
+            ElasticTileType::B | ElasticTileType::D => Some(PI - FRAC_PI_4),
+        };
+    }
 }
 
 impl ToSVG for ElasticTriangleTile {
     fn to_svg_node(&self) -> Box<dyn Node> {
-        let (mut x, mut y) = match self.tile_type {
-            ElasticTileType::A => (vec![1_f32, 0_f32, 0_f32], vec![1_f32, 1_f32, 0_f32]),
-            ElasticTileType::B => (vec![0_f32, 0_f32, 1_f32], vec![1_f32, 0_f32, 0_f32]),
-            ElasticTileType::C => (vec![0_f32, 1_f32, 1_f32], vec![0_f32, 0_f32, 1_f32]),
-            ElasticTileType::D => (vec![1_f32, 1_f32, 0_f32], vec![0_f32, 1_f32, 1_f32])
-        };
+        let (x, y) = self.corners();
+        let (mut x, mut y) = (x.to_vec(), y.to_vec());
 
         let t = self.point();
         x.push(t.x());
@@ -147,3 +241,35 @@ impl Distribution<ElasticTriangleTile> for Standard {
         }
     }
 }
+
+impl ToPostScript for ElasticTriangleTile {
+    /// Type A triangle turned around the cell centre, takes `t`, quarter turns and cell origin.
+    fn postscript_procedures(&self) -> &'static str {
+        return "/tri { gsave translate 90 mul 0.5 0.5 translate rotate -0.5 -0.5 translate\n  \
+            0.5 mul 0.25 add 1 1 m 0 1 l 0 0 l dup 1 exch sub exch l h f grestore } bind def";
+    }
+
+    fn to_postscript(&self, origin: Vec2<f32>) -> String {
+        let quarter_turns = match self.tile_type {
+            ElasticTileType::A => 0,
+            ElasticTileType::B => 1,
+            ElasticTileType::C => 2,
+            ElasticTileType::D => 3,
+        };
+
+        return format!("{} {} {} {} tri\n", format_number(self.t), quarter_turns, format_number(origin.x()), format_number(origin.y()));
+    }
+}
+
+impl ToText for ElasticTriangleTile {
+    fn to_text(&self) -> [char; 2] {
+        let triangle = match self.tile_type {
+            ElasticTileType::A => '◣',
+            ElasticTileType::B => '◤',
+            ElasticTileType::C => '◥',
+            ElasticTileType::D => '◢',
+        };
+
+        return [triangle; 2];
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{vec2::Vec2, geometry::Segment, tile::traits::Tile, utils::format_number};
+
+/// Procedures used by [`outline_postscript`], always defined in the prolog.
+pub(crate) const PATH_PROCEDURES: &str = "/m { moveto } bind def\n/l { lineto } bind def\n/c { curveto } bind def\n/h { closepath } bind def\n/f { fill } bind def";
+
+pub trait ToPostScript: Tile {
+    /// Procedures shared by all tiles of the type, they are defined once in the prolog.
+    fn postscript_procedures(&self) -> &'static str {
+        return "";
+    }
+
+    /// Code drawing the tile in the cell with the given origin, `y` axis points down.
+    ///
+    /// Tiles draw their outlines by default, tile types with procedures only call them with parameters.
+    fn to_postscript(&self, origin: Vec2<f32>) -> String {
+        return self.outlines()
+            .iter()
+            .map(|outline| outline_postscript(outline.transformed(1.0, origin).with_cubic_arcs().segments()))
+            .collect();
+    }
+}
+
+/// Filled path of segments, arcs must be converted to curves.
+pub(crate) fn outline_postscript(segments: &[Segment]) -> String {
+    let mut code = String::new();
+
+    for segment in segments {
+        match *segment {
+            Segment::MoveTo(p) => code += &format!("{} {} m ", format_number(p.x()), format_number(p.y())),
+            Segment::LineTo(p) => code += &format!("{} {} l ", format_number(p.x()), format_number(p.y())),
+            Segment::CubicTo(c1, c2, end) => code += &format!(
+                "{} {} {} {} {} {} c ",
+                format_number(c1.x()), format_number(c1.y()), format_number(c2.x()), format_number(c2.y()), format_number(end.x()), format_number(end.y())
+            ),
+            Segment::Close => code += "h ",
+            Segment::ArcTo { .. } => unreachable!("arcs are converted to curves"),
+        }
+    }
+
+    if !code.is_empty() {
+        code += "f\n";
+    }
+
+    return code;
+}
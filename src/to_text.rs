@@ -0,0 +1,33 @@
+use crate::{vec2::Vec2, tile::traits::Tile};
+
+/// Quadrant block characters indexed by filled quadrants: top left is 1, top right 2, bottom left 4 and bottom right 8.
+const QUADRANTS: [char; 16] = [' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█'];
+/// Points sampled along every axis of a quadrant.
+const QUADRANT_SAMPLES: usize = 2;
+
+pub trait ToText: Tile {
+    /// Two characters drawing the tile, two columns of terminal keep cells roughly square.
+    ///
+    /// By default the cell is split into 4 by 2 parts drawn with quadrant block characters.
+    fn to_text(&self) -> [char; 2] {
+        let outlines = self.outlines();
+        let filled = |col: usize, row: usize| -> usize {
+            let mut covered = 0;
+            for i in 0..QUADRANT_SAMPLES {
+                for j in 0..QUADRANT_SAMPLES {
+                    let point = Vec2::new(
+                        (col as f32 + (i as f32 + 0.5) / QUADRANT_SAMPLES as f32) / 4.0,
+                        (row as f32 + (j as f32 + 0.5) / QUADRANT_SAMPLES as f32) / 2.0
+                    );
+                    if outlines.iter().any(|outline| outline.contains(point)) {
+                        covered += 1;
+                    }
+                }
+            }
+
+            return (2 * covered >= QUADRANT_SAMPLES * QUADRANT_SAMPLES) as usize;
+        };
+
+        return [0, 2].map(|col| QUADRANTS[filled(col, 0) | filled(col + 1, 0) << 1 | filled(col, 1) << 2 | filled(col + 1, 1) << 3]);
+    }
+}
@@ -1,67 +1,338 @@
-use svg::{node::element::Group, Node};
+use std::ops::{Index, IndexMut, Range};
 
-use crate::{vec2::Vec2, image::Image, generator::{Generator}, to_svg::ToSVG, utils::flatten_2d_index};
+use svg::{node::element::{Group, Path}, Node};
 
+use crate::{vec2::Vec2, coverage::CoverageMap, strand::{Strand, trace_strands}, image::{Image, BlockSample, Sampling}, dither::dither, generator::{Generator, PatternGenerator}, tile::{traits::Tile, placed::PlacedTile}, to_svg::ToSVG, utils::flatten_2d_index};
+
+/// Grid of tiles laid out by the generator for the source image.
+///
+/// Tiles are stored in a single grid row by row, generator is kept only for its block sizes.
+/// With `serde` feature the generated grid can be stored and rendered later without the source image.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TruchetImageData<TGenerator>", bound(
+    serialize = "TGenerator: serde::Serialize, TGenerator::TileType: serde::Serialize",
+    deserialize = "TGenerator: serde::Deserialize<'de>, TGenerator::TileType: serde::Deserialize<'de>"
+)))]
 pub struct TruchetImage<TGenerator: Generator> {
-    generators_size: Vec2<usize>,
-    generator: TGenerator, 
-    generators: Vec<TGenerator>
+    /// Number of tile rows and columns.
+    grid_size: Vec2<usize>,
+    generator: TGenerator,
+    sampling: Sampling,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::tile::template::serialize_tiles"))]
+    tiles: Vec<TGenerator::TileType>
+}
+
+/// Deserialized image before its grid is checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(bound(deserialize = "TGenerator: serde::Deserialize<'de>, TGenerator::TileType: serde::Deserialize<'de>"))]
+struct TruchetImageData<TGenerator: Generator> {
+    grid_size: Vec2<usize>,
+    generator: TGenerator,
+    #[serde(default)]
+    sampling: Sampling,
+    #[serde(deserialize_with = "crate::tile::template::deserialize_tiles")]
+    tiles: Vec<TGenerator::TileType>
 }
 
+#[cfg(feature = "serde")]
+impl<TGenerator: Generator> TryFrom<TruchetImageData<TGenerator>> for TruchetImage<TGenerator> {
+    type Error = String;
+
+    fn try_from(data: TruchetImageData<TGenerator>) -> Result<Self, Self::Error> {
+        let expected = data.grid_size.x() * data.grid_size.y();
+        if data.tiles.len() != expected {
+            return Err(format!("image of {}x{} tiles needs {} tiles, got {}", data.grid_size.x(), data.grid_size.y(), expected, data.tiles.len()));
+        }
+
+        return Ok(Self { grid_size: data.grid_size, generator: data.generator, sampling: data.sampling, tiles: data.tiles });
+    }
+}
+
+/// Generates image with tiles of every generator block sharing the sample of the source image block.
 pub fn generate<TImage: Image, TGenerator: Generator>(image: &TImage, generator: TGenerator) -> TruchetImage<TGenerator> {
+    return generate_with_sampling(image, generator, Sampling::Block);
+}
+
+/// Generates image sampling the source image per block or per tile, see [`Sampling`].
+pub fn generate_with_sampling<TImage: Image, TGenerator: Generator>(image: &TImage, mut generator: TGenerator, sampling: Sampling) -> TruchetImage<TGenerator> {
     let generators_size = image.size() / generator.source_image_block_size();
-    let mut truchet = TruchetImage {
-        generators_size,
-        generator,
-        generators: Vec::with_capacity(generators_size.x() * generators_size.y())
+    let source_block_size = generator.source_image_block_size();
+    let block_size = generator.generator_block_size();
+    let grid_size = Vec2::new(generators_size.y() * block_size.x(), generators_size.x() * block_size.y());
+
+    // Samples are stored by columns as expected by dithering, `x` is the column of the sample
+    let samples_size = match sampling {
+        Sampling::Block => generators_size,
+        Sampling::Tile => Vec2::new(grid_size.y(), grid_size.x()),
+    };
+    let sample_cell = |row: usize, col: usize| match sampling {
+        Sampling::Block => Vec2::new(col / block_size.y(), row / block_size.x()),
+        Sampling::Tile => Vec2::new(col, row),
     };
 
-    let source_block_size = truchet.generator.source_image_block_size().x() * truchet.generator.source_image_block_size().y();
-
-    for generator_x in 0..generators_size.x() {
-        for generator_y in 0..generators_size.y() {
-            let start_x = truchet.generator.source_image_block_size().x() * generator_x;
-            let start_y = truchet.generator.source_image_block_size().y() * generator_y;
-            let end_x = start_x + truchet.generator.source_image_block_size().x();
-            let end_y = start_y + truchet.generator.source_image_block_size().y();
-            
-            let mut brightness = 0.0;
-            for pixel_x in start_x..end_x {
-                for pixel_y in start_y..end_y {
-                    brightness += image.get_pixel_brightness((pixel_x, pixel_y).into());
-                }
+    let mut samples = Vec::with_capacity(samples_size.x() * samples_size.y());
+    for x in 0..samples_size.x() {
+        for y in 0..samples_size.y() {
+            let (row, col) = match sampling {
+                Sampling::Block => (y * block_size.x(), x * block_size.y()),
+                Sampling::Tile => (y, x),
+            };
+            let (start, size) = sampling.region(row, col, block_size, source_block_size);
+            samples.push(BlockSample::sample(image, start, size));
+        }
+    }
+
+    if let Some(mut levels) = generator.brightness_levels().filter(|levels| !levels.is_empty()) {
+        levels.sort_by(f32::total_cmp);
+        let mut brightness: Vec<f32> = samples.iter().map(|sample| sample.brightness()).collect();
+        dither(&mut brightness, samples_size, &levels, generator.dithering());
+
+        for (sample, brightness) in samples.iter_mut().zip(brightness) {
+            *sample = sample.with_brightness(brightness);
+        }
+    }
+
+    generator.prepare(grid_size);
+
+    // Tiles are laid out by their absolute position, so nothing but the tiles is kept per block
+    let mut tiles = Vec::with_capacity(grid_size.x() * grid_size.y());
+    for row in 0..grid_size.x() {
+        for col in 0..grid_size.y() {
+            let cell = sample_cell(row, col);
+            let sample = &samples[flatten_2d_index(cell.x(), cell.y(), samples_size.y())];
+            tiles.push(match sampling {
+                Sampling::Block => generator.layout(row, col, sample),
+                Sampling::Tile => generator.layout_tile(row, col, sample),
+            });
+        }
+    }
+
+    return TruchetImage { grid_size, generator, sampling, tiles };
+}
+
+impl<TGenerator: Generator> TruchetImage<TGenerator> {
+    /// Number of tile rows and columns of the whole image.
+    #[inline]
+    pub fn grid_size(&self) -> Vec2<usize> {
+        return self.grid_size;
+    }
+
+    #[inline]
+    pub(crate) fn tile_at(&self, row: usize, col: usize) -> &TGenerator::TileType {
+        return &self.tiles[flatten_2d_index(row, col, self.grid_size.y())];
+    }
+
+    /// Tile at the given row and column of the whole image, `None` if it is out of the image.
+    pub fn get(&self, row: usize, col: usize) -> Option<&TGenerator::TileType> {
+        let grid_size = self.grid_size();
+        if row >= grid_size.x() || col >= grid_size.y() {
+            return None;
+        }
+
+        return Some(self.tile_at(row, col));
+    }
+
+    /// Tiles of the whole image row by row.
+    pub fn tiles(&self) -> impl Iterator<Item = PlacedTile<'_, TGenerator::TileType>> {
+        let cols = self.grid_size.y();
+        return self.tiles.iter().enumerate().map(move |(i, tile)| PlacedTile::new(i / cols, i % cols, tile));
+    }
+
+    /// Number of rows and columns of generator blocks, blocks at the borders may be partial.
+    pub fn generator_grid_size(&self) -> Vec2<usize> {
+        let block = self.generator.generator_block_size();
+        return Vec2::new(self.grid_size.x().div_ceil(block.x().max(1)), self.grid_size.y().div_ceil(block.y().max(1)));
+    }
+
+    /// Generator which laid out the tiles.
+    #[inline]
+    pub fn generator(&self) -> &TGenerator {
+        return &self.generator;
+    }
+
+    #[inline]
+    pub fn sampling(&self) -> Sampling {
+        return self.sampling;
+    }
+
+    /// Mutable tile at the given row and column, `None` if it is out of the image.
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut TGenerator::TileType> {
+        let grid_size = self.grid_size();
+        if row >= grid_size.x() || col >= grid_size.y() {
+            return None;
+        }
+
+        return Some(self.tile_at_mut(row, col));
+    }
+
+    /// Applies edit to every tile of the rectangular region, parts of the region out of the image are skipped.
+    pub fn edit(&mut self, rows: Range<usize>, cols: Range<usize>, mut edit: impl FnMut(&mut TGenerator::TileType)) {
+        let grid_size = self.grid_size();
+
+        for row in rows.start..rows.end.min(grid_size.x()) {
+            for col in cols.start..cols.end.min(grid_size.y()) {
+                edit(self.tile_at_mut(row, col));
+            }
+        }
+    }
+
+    /// Sets brightness of every tile of the region, e.g. to lighten a face area.
+    pub fn set_brightness(&mut self, rows: Range<usize>, cols: Range<usize>, brightness: f32) {
+        self.edit(rows, cols, |tile| tile.set_brightness(brightness));
+    }
+
+    /// Rotates every tile of the region by 90 degrees clockwise in its cell.
+    pub fn rotate(&mut self, rows: Range<usize>, cols: Range<usize>) {
+        self.edit(rows, cols, |tile| tile.rotate());
+    }
+
+    /// Replaces every tile of the region with the given tile.
+    pub fn replace(&mut self, rows: Range<usize>, cols: Range<usize>, tile: &TGenerator::TileType) {
+        self.edit(rows, cols, |current| *current = tile.clone());
+    }
+
+    /// Stamps the pattern onto the region, pattern starts in the top left corner of the region and is repeated to fill it.
+    ///
+    /// Tiles are copied as they are, use [`TruchetImage::set_brightness`] to match tone of the rest of the image.
+    pub fn overlay(&mut self, rows: Range<usize>, cols: Range<usize>, pattern: &PatternGenerator<TGenerator::TileType>) {
+        let pattern_size = pattern.generator_block_size();
+        if pattern_size.x() == 0 || pattern_size.y() == 0 {
+            return;
+        }
+
+        let grid_size = self.grid_size();
+        for row in rows.start..rows.end.min(grid_size.x()) {
+            for col in cols.start..cols.end.min(grid_size.y()) {
+                let pattern_pos = ((row - rows.start) % pattern_size.x(), (col - cols.start) % pattern_size.y());
+                *self.tile_at_mut(row, col) = pattern[pattern_pos].clone();
             }
+        }
+    }
 
-            brightness /= source_block_size as f32;
-            
-            truchet.generators.push(truchet.generator.clone_with_brightness(brightness));
+    #[inline]
+    fn tile_at_mut(&mut self, row: usize, col: usize) -> &mut TGenerator::TileType {
+        return &mut self.tiles[flatten_2d_index(row, col, self.grid_size.y())];
+    }
+
+    /// Top left pixel and size of the source image region the tile was generated from.
+    #[inline]
+    pub(crate) fn source_block(&self, row: usize, col: usize) -> (Vec2<usize>, Vec2<usize>) {
+        return self.sampling.region(row, col, self.generator.generator_block_size(), self.generator.source_image_block_size());
+    }
+
+    /// Traces strands running through tiles, e.g. loops formed by arcs of circle tiles.
+    pub fn strands(&self) -> Vec<Strand> {
+        let grid_size = self.grid_size();
+        return trace_strands(grid_size, |row, col| self.tile_at(row, col));
+    }
+
+    /// Fills background regions between strands alternately with two colours, by parity of the cell corners.
+    ///
+    /// Only circle and knot tiles, whose strands run around the corners, have regions, also when wrapped by other tiles.
+    /// Other tiles, e.g. triangles of diagonal layouts, leave both groups empty.
+    pub fn regions_to_svg(&self, colours: [&str; 2]) -> Box<dyn Node> {
+        let mut groups = colours.map(|colour| Group::new().set("fill", colour));
+
+        for placed in self.tiles() {
+            for region in placed.tile.regions() {
+                let outline = region.outline.transformed(1.0, placed.origin());
+                groups[region.parity(placed.row, placed.col)].append(Path::new().set("d", outline.to_path_data()));
+            }
         }
+
+        let [even, odd] = groups;
+        return Box::new(Group::new().add(even).add(odd));
     }
 
-    return truchet;
+    /// Measures how much of every generator block is covered by tiles.
+    pub fn coverage_map(&self) -> CoverageMap {
+        let block = self.generator.generator_block_size();
+        let blocks = self.generator_grid_size();
+        let mut coverage = Vec::with_capacity(blocks.x() * blocks.y());
+
+        // Blocks are stored by columns
+        for block_col in 0..blocks.y() {
+            for block_row in 0..blocks.x() {
+                let rows = block_row * block.x()..((block_row + 1) * block.x()).min(self.grid_size.x());
+                let cols = block_col * block.y()..((block_col + 1) * block.y()).min(self.grid_size.y());
+                let count = rows.len() * cols.len();
+                let sum: f32 = rows.flat_map(|row| cols.clone().map(move |col| (row, col)))
+                    .map(|(row, col)| self.tile_at(row, col).coverage())
+                    .sum();
+
+                coverage.push(if count > 0 { sum / count as f32 } else { 0.0 });
+            }
+        }
+
+        return CoverageMap::new(Vec2::new(blocks.y(), blocks.x()), self.generator.source_image_block_size(), coverage);
+    }
+}
+
+impl<TGenerator: Generator> Index<(usize, usize)> for TruchetImage<TGenerator> {
+    type Output = TGenerator::TileType;
+
+    /// Tile at row and column, panics if it is out of the image.
+    #[inline]
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        return self.get(row, col).expect("Tile position is out of the image");
+    }
+}
+
+impl<TGenerator: Generator> IndexMut<(usize, usize)> for TruchetImage<TGenerator> {
+    #[inline]
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        return self.get_mut(row, col).expect("Tile position is out of the image");
+    }
+}
+
+impl<TGenerator: Generator> TruchetImage<TGenerator> where TGenerator::TileType: ToSVG {
+    /// Renders tiles over their background regions filled with two colours, see [`TruchetImage::regions_to_svg`].
+    pub fn to_two_coloured_svg_node(&self, colours: [&str; 2]) -> Box<dyn Node> {
+        return Box::new(Group::new()
+            .add(self.regions_to_svg(colours))
+            .add(self.to_svg_node()));
+    }
 }
 
-impl<TGenerator: Generator + ToSVG> ToSVG for TruchetImage<TGenerator> {
+impl<TGenerator: Generator> ToSVG for TruchetImage<TGenerator> where TGenerator::TileType: ToSVG {
     fn to_svg_node(&self) -> Box<dyn Node> {
         let mut g = Group::new();
-    
-        for gen_x in 0..self.generators_size.x() {
-            for gen_y in 0..self.generators_size.y() {
-                let pattern_origin = Vec2::new(
-                    (self.generator.generator_block_size().y() * gen_x) as f32,
-                    (self.generator.generator_block_size().x() * gen_y) as f32
-                );
-
-                let gen = self.generators[flatten_2d_index(gen_x, gen_y, self.generators_size.y())]
-                    .to_svg_node();
-                let gen_translated = Group::new()
-                    .set("transform", format!("translate({} {})", pattern_origin.x(), pattern_origin.y()))
-                    .add(gen);
-
-                g.append(gen_translated);
-            }
+
+        for placed in self.tiles() {
+            g.append(placed.to_svg_node());
         }
 
         return Box::new(g);
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::{generator::{self, PatternGenerator}, image::fixtures::Gradient, tile::triangle::ElasticTriangleTile, to_svg::ToSVG, vec2::Vec2};
+
+    use super::{generate, TruchetImage};
+
+    type FanImage = TruchetImage<PatternGenerator<ElasticTriangleTile>>;
+
+    #[test]
+    fn image_round_trips_through_json() {
+        let image = generate(&Gradient(Vec2::new(16, 8)), generator::fan(Vec2::new(4, 4)));
+        let restored: FanImage = serde_json::from_str(&serde_json::to_string(&image).unwrap()).unwrap();
+
+        assert_eq!(restored.grid_size(), image.grid_size());
+        assert_eq!(restored.sampling(), image.sampling());
+        assert_eq!(restored.to_svg_node().to_string(), image.to_svg_node().to_string());
+    }
+
+    #[test]
+    fn image_with_wrong_tiles_count_is_rejected() {
+        let image = generate(&Gradient(Vec2::new(16, 8)), generator::fan(Vec2::new(4, 4)));
+        let expected = image.grid_size().x() * image.grid_size().y();
+        let mut value = serde_json::to_value(&image).unwrap();
+        value["tiles"].as_array_mut().unwrap().pop();
+
+        let error = serde_json::from_value::<FanImage>(value).err().unwrap();
+        assert!(error.to_string().contains(&format!("needs {} tiles, got {}", expected, expected - 1)), "{}", error);
+    }
+}
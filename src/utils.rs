@@ -3,3 +3,11 @@
 pub fn flatten_2d_index(row: usize, col: usize, cols_count: usize) -> usize {
     return row * cols_count + col;
 }
+
+/// Formats number without trailing zeros and exponent, as PDF and PostScript expect.
+pub(crate) fn format_number(value: f32) -> String {
+    let formatted = format!("{:.4}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+
+    return if trimmed == "-0" || trimmed.is_empty() { "0".to_owned() } else { trimmed.to_owned() };
+}
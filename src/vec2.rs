@@ -1,6 +1,7 @@
-use std::ops::{Rem, Mul, Div, Add};
+use std::ops::{Rem, Mul, Div, Add, Sub};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec2<T> {
     x: T,
     y: T
@@ -67,6 +68,15 @@ impl<T: Copy + Add<Output = T>> Add for Vec2<T> {
     }
 }
 
+impl<T: Copy + Sub<Output = T>> Sub for Vec2<T> {
+    type Output = Vec2<T>;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        return Self::Output::new(self.x - rhs.x, self.y - rhs.y);
+    }
+}
+
 impl<T: Copy> From<(T, T)> for Vec2<T> {
     #[inline]
     fn from(value: (T, T)) -> Self {